@@ -1,15 +1,30 @@
 use crate::board::BOARD_SIZE;
+use crate::color::Color;
 use crate::square::Square;
 use colored::Colorize;
 use std::fmt::{Display, Formatter};
 use std::iter::FusedIterator;
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr};
 
+mod magics;
+
+pub use magics::{bishop_attacks, rook_attacks};
+
 #[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
 #[repr(transparent)]
 pub struct Bitboard(pub u64);
 
 impl Bitboard {
+    pub const FILE_A: Self = Self(0x0101_0101_0101_0101);
+    pub const FILE_H: Self = Self(0x8080_8080_8080_8080);
+
+    pub const RANK_1: Self = Self(0x0000_0000_0000_00FF);
+    pub const RANK_2: Self = Self(0x0000_0000_0000_FF00);
+    pub const RANK_7: Self = Self(0x00FF_0000_0000_0000);
+    pub const RANK_8: Self = Self(0xFF00_0000_0000_0000);
+
+    pub const LIGHT_SQUARES: Self = Self(0x55AA_55AA_55AA_55AA);
+
     pub fn new(bb: u64) -> Self {
         Self(bb)
     }
@@ -48,6 +63,171 @@ impl Bitboard {
     pub fn remove(&mut self, square: Square) {
         *self = self.bitand(!square.bitboard());
     }
+
+    /// Returns the least significant set square, or [Square::None] if the bitboard is empty.
+    pub fn lsb(&self) -> Square {
+        if self.is_empty() {
+            return Square::None;
+        }
+        Square::new(self.0.trailing_zeros() as u8)
+    }
+}
+
+static PAWN_ATTACKS: [[Bitboard; 64]; 2] = [
+    init_leaper_attacks(&[(-1, 1), (1, 1)]),
+    init_leaper_attacks(&[(-1, -1), (1, -1)]),
+];
+
+static KNIGHT_ATTACKS: [Bitboard; 64] = init_leaper_attacks(&[
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+]);
+
+static KING_ATTACKS: [Bitboard; 64] = init_leaper_attacks(&[
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+]);
+
+static BETWEEN: [[Bitboard; 64]; 64] = init_between();
+static LINE: [[Bitboard; 64]; 64] = init_line();
+
+pub fn pawn_attacks(square: Square, color: Color) -> Bitboard {
+    PAWN_ATTACKS[color][square]
+}
+
+pub fn knight_attacks(square: Square) -> Bitboard {
+    KNIGHT_ATTACKS[square]
+}
+
+pub fn king_attacks(square: Square) -> Bitboard {
+    KING_ATTACKS[square]
+}
+
+pub fn queen_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    bishop_attacks(square, occupancy) | rook_attacks(square, occupancy)
+}
+
+/// Returns the squares strictly between `a` and `b` if both share a rank, file or diagonal,
+/// otherwise an empty bitboard.
+pub fn between(a: Square, b: Square) -> Bitboard {
+    BETWEEN[a][b]
+}
+
+/// Returns the full line from edge to edge going through `a` and `b` if both share a rank,
+/// file or diagonal, otherwise an empty bitboard.
+pub fn line(a: Square, b: Square) -> Bitboard {
+    LINE[a][b]
+}
+
+const fn init_leaper_attacks(deltas: &[(i8, i8)]) -> [Bitboard; 64] {
+    let mut table = [Bitboard(0); 64];
+
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < deltas.len() {
+            let (file_delta, rank_delta) = deltas[i];
+            let file = (square % 8) as i8 + file_delta;
+            let rank = (square / 8) as i8 + rank_delta;
+            if file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                table[square].0 |= 1 << (rank * 8 + file);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+
+    table
+}
+
+/// Returns the `(file, rank)` step leading from `a` towards `b`, or `None` if both squares
+/// do not share a rank, file or diagonal.
+const fn direction(a: usize, b: usize) -> Option<(i8, i8)> {
+    let file_delta = (b % 8) as i8 - (a % 8) as i8;
+    let rank_delta = (b / 8) as i8 - (a / 8) as i8;
+
+    if a == b || (file_delta != 0 && rank_delta != 0 && file_delta.abs() != rank_delta.abs()) {
+        return None;
+    }
+
+    Some((file_delta.signum(), rank_delta.signum()))
+}
+
+const fn init_between() -> [[Bitboard; 64]; 64] {
+    let mut table = [[Bitboard(0); 64]; 64];
+
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            if let Some(direction) = direction(a, b) {
+                let blocked = sliding_attacks(a, 1 << b, &[direction]);
+                table[a][b] = Bitboard(blocked & !(1 << b));
+            }
+            b += 1;
+        }
+        a += 1;
+    }
+
+    table
+}
+
+const fn init_line() -> [[Bitboard; 64]; 64] {
+    let mut table = [[Bitboard(0); 64]; 64];
+
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            if let Some((file_delta, rank_delta)) = direction(a, b) {
+                let ray = sliding_attacks(a, 0, &[(file_delta, rank_delta)]);
+                let opposite = sliding_attacks(a, 0, &[(-file_delta, -rank_delta)]);
+                table[a][b] = Bitboard(ray | opposite | 1 << a);
+            }
+            b += 1;
+        }
+        a += 1;
+    }
+
+    table
+}
+
+/// Walks every `(file, rank)` direction from `square` until it leaves the board or hits a
+/// blocker. The blocker itself is included in the attacks.
+const fn sliding_attacks(square: usize, occupancy: u64, directions: &[(i8, i8)]) -> u64 {
+    let mut attacks = 0;
+
+    let mut i = 0;
+    while i < directions.len() {
+        let (file_delta, rank_delta) = directions[i];
+        let mut file = (square % 8) as i8 + file_delta;
+        let mut rank = (square / 8) as i8 + rank_delta;
+
+        while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+            let target = 1 << (rank * 8 + file);
+            attacks |= target;
+            if occupancy & target != 0 {
+                break;
+            }
+            file += file_delta;
+            rank += rank_delta;
+        }
+        i += 1;
+    }
+
+    attacks
 }
 
 impl Display for Bitboard {
@@ -0,0 +1,333 @@
+use crate::bitboard::{Bitboard, sliding_attacks};
+use crate::square::Square;
+
+pub(super) const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+pub(super) const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+const ROOK_TABLE_SIZE: usize = 102_400;
+const BISHOP_TABLE_SIZE: usize = 5_248;
+
+/// Fancy magic bitboard entry of a single square, see
+/// [here](https://www.chessprogramming.org/Magic_Bitboards#Fancy).
+///
+/// Every magic maps the relevant occupancy onto exactly `mask.count_ones()` bits, so the
+/// attack sets of all squares are packed into one shared table starting at `offset`.
+#[derive(Copy, Clone)]
+struct Magic {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+/// The `pext` feature replaces the magic multiplication with the BMI2 `pext` instruction. It
+/// only takes effect if the target supports BMI2 (e.g. `-C target-cpu=native` on a modern
+/// x86_64 CPU), otherwise the magic lookup is used as a fallback.
+const USE_PEXT: bool = cfg!(all(
+    feature = "pext",
+    target_arch = "x86_64",
+    target_feature = "bmi2"
+));
+
+impl Magic {
+    const fn magic_index(&self, occupancy: u64) -> usize {
+        self.offset + ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+
+    #[cfg(not(all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2")))]
+    #[inline(always)]
+    fn index(&self, occupancy: u64) -> usize {
+        self.magic_index(occupancy)
+    }
+
+    #[cfg(all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2"))]
+    #[inline(always)]
+    fn index(&self, occupancy: u64) -> usize {
+        // SAFETY: BMI2 support is guaranteed by the target_feature cfg above
+        self.offset + unsafe { std::arch::x86_64::_pext_u64(occupancy, self.mask) } as usize
+    }
+}
+
+static ROOK_ENTRIES: [Magic; 64] = init_magics(&ROOK_DIRECTIONS, &ROOK_MAGICS);
+static BISHOP_ENTRIES: [Magic; 64] = init_magics(&BISHOP_DIRECTIONS, &BISHOP_MAGICS);
+
+// Building the rook table takes a few seconds of const evaluation.
+#[allow(long_running_const_eval)]
+static ROOK_TABLE: [Bitboard; ROOK_TABLE_SIZE] = init_table(&ROOK_ENTRIES, &ROOK_DIRECTIONS);
+static BISHOP_TABLE: [Bitboard; BISHOP_TABLE_SIZE] =
+    init_table(&BISHOP_ENTRIES, &BISHOP_DIRECTIONS);
+
+pub fn rook_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    ROOK_TABLE[ROOK_ENTRIES[square].index(occupancy.0)]
+}
+
+pub fn bishop_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    BISHOP_TABLE[BISHOP_ENTRIES[square].index(occupancy.0)]
+}
+
+/// Relevant occupancy of a slider: every ray square except the last one before the edge,
+/// since a blocker on the edge does not change the attacks.
+const fn relevant_mask(square: usize, directions: &[(i8, i8)]) -> u64 {
+    let mut mask = 0;
+    let mut i = 0;
+    while i < directions.len() {
+        let (file_delta, rank_delta) = directions[i];
+        let mut file = (square % 8) as i8 + file_delta;
+        let mut rank = (square / 8) as i8 + rank_delta;
+        while file + file_delta >= 0
+            && file + file_delta < 8
+            && rank + rank_delta >= 0
+            && rank + rank_delta < 8
+        {
+            mask |= 1 << (rank * 8 + file);
+            file += file_delta;
+            rank += rank_delta;
+        }
+        i += 1;
+    }
+    mask
+}
+
+const fn init_magics(directions: &[(i8, i8)], magics: &[u64; 64]) -> [Magic; 64] {
+    let mut entries = [Magic {
+        mask: 0,
+        magic: 0,
+        shift: 0,
+        offset: 0,
+    }; 64];
+
+    let mut offset = 0;
+    let mut square = 0;
+    while square < 64 {
+        let mask = relevant_mask(square, directions);
+        entries[square] = Magic {
+            mask,
+            magic: magics[square],
+            shift: 64 - mask.count_ones(),
+            offset,
+        };
+        offset += 1 << mask.count_ones();
+        square += 1;
+    }
+
+    entries
+}
+
+/// Fills the attack table by enumerating every subset of each relevant mask with the
+/// Carry-Rippler trick. Fails to compile if a magic maps two different attack sets onto the
+/// same index.
+///
+/// The Carry-Rippler trick yields the subsets in ascending order, which is exactly the order
+/// PEXT extracts them in, so the n-th subset is stored at index n for the PEXT backend.
+const fn init_table<const N: usize>(
+    entries: &[Magic; 64],
+    directions: &[(i8, i8)],
+) -> [Bitboard; N] {
+    let mut table = [Bitboard(0); N];
+
+    let mut square = 0;
+    while square < 64 {
+        let entry = entries[square];
+        let mut subset: u64 = 0;
+        let mut n = 0;
+        loop {
+            let attacks = sliding_attacks(square, subset, directions);
+            let index = if USE_PEXT {
+                entry.offset + n
+            } else {
+                entry.magic_index(subset)
+            };
+            assert!(
+                table[index].0 == 0 || table[index].0 == attacks,
+                "Magic collision"
+            );
+            table[index] = Bitboard(attacks);
+            n += 1;
+
+            subset = subset.wrapping_sub(entry.mask) & entry.mask;
+            if subset == 0 {
+                break;
+            }
+        }
+        square += 1;
+    }
+
+    table
+}
+
+#[rustfmt::skip]
+const ROOK_MAGICS: [u64; 64] = [
+    0x1080004008801020,
+    0x0840092002C03000,
+    0x1900200010400900,
+    0x0880100008000480,
+    0x4200100420080200,
+    0x8100020100080400,
+    0x0200040110886200,
+    0x0200008040220411,
+    0x0404800084400220,
+    0x0000401000402000,
+    0x0086001081220440,
+    0x0408800800100280,
+    0x000A001201040820,
+    0x8848800200840080,
+    0x4001000100040200,
+    0x0442000102105084,
+    0x9080010020804100,
+    0x0040404000201009,
+    0x0000808010002009,
+    0x2200090021D00100,
+    0x0008008008040080,
+    0x0004004002010040,
+    0x0011040008015042,
+    0x00000A0001768104,
+    0x0000800080204009,
+    0x2010004140002001,
+    0x9800200280100080,
+    0x1000100080080080,
+    0x0442000A00049020,
+    0x2100040080020080,
+    0x0800120400900148,
+    0x0010040A00128541,
+    0x2800804000800030,
+    0x1010002000400041,
+    0x4000200011004100,
+    0x0610008410800800,
+    0x0400802402800800,
+    0xC100020080800400,
+    0x0002000802000401,
+    0x0182085882000401,
+    0x0220204000808000,
+    0x2860100040024022,
+    0x0001002004110040,
+    0x99101042000A0020,
+    0x0004080004008080,
+    0x0010040002008080,
+    0x2012004881020004,
+    0x8300842444820011,
+    0x0088403882010200,
+    0x0820400080210100,
+    0x0110910040A00300,
+    0x0801100280080480,
+    0x0242009008200600,
+    0x1002000489500200,
+    0x0040800200010080,
+    0x0091800041000080,
+    0x0000209300488001,
+    0x04C1002414824001,
+    0x020020000B001041,
+    0x7000100004200901,
+    0x8002002004100802,
+    0x30010002084C0007,
+    0x0888221800813004,
+    0x4000002840840112,
+];
+
+#[rustfmt::skip]
+const BISHOP_MAGICS: [u64; 64] = [
+    0xA010041108003100,
+    0x006082020A002900,
+    0x6810010619200000,
+    0x08281A0520000408,
+    0x0001104001000400,
+    0x0018901008048400,
+    0x00040A0210245280,
+    0x000200210808A402,
+    0x9140048410821200,
+    0x0800091010820041,
+    0x20504804832202C0,
+    0x0100091401081000,
+    0x8021011140000012,
+    0x0810020804450400,
+    0x208B0542109008A2,
+    0x0080084A08040204,
+    0x0040E2A80811244C,
+    0x2505022008008108,
+    0x0430220100420040,
+    0x010A040420220040,
+    0x1105000290400000,
+    0x0093001200822120,
+    0x4000A62048043004,
+    0x280120048A015004,
+    0x006090002A020814,
+    0x44042000240800D0,
+    0x01102800040A4400,
+    0x1004080080220040,
+    0x0001001011004024,
+    0x0010044000805040,
+    0x0914041200820100,
+    0x0004821012821480,
+    0x0024040500C05021,
+    0x0088611002080200,
+    0x0116080A00040020,
+    0x4000020080080080,
+    0x2450450140840040,
+    0x0000880201484100,
+    0x0222020404020092,
+    0x8081110600002E00,
+    0x2842101105000801,
+    0x1100809008001025,
+    0x00020202221C0400,
+    0x0422014022009020,
+    0x0210046102100C00,
+    0xC004008082029102,
+    0x00AA461801101200,
+    0x0404080080201108,
+    0x020542108C205002,
+    0x0410544804100100,
+    0x0040910841100000,
+    0x0400200042021100,
+    0x00004204850400C0,
+    0x0200100410A42102,
+    0x1040020801210102,
+    0x0805040410420000,
+    0x2884804130100200,
+    0x800C262201242000,
+    0x1058000194108800,
+    0x0014221054420204,
+    0x0104000012A02200,
+    0x0200881003300100,
+    0x0140400202840100,
+    0x0402020801010201,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookups_match_ray_walk() {
+        // Simple xorshift to get reproducible occupancies
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        for _ in 0..1000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let occupancy = state & (state >> 3);
+
+            for square in 0..64 {
+                let sq = Square::new(square as u8);
+                assert_eq!(
+                    rook_attacks(sq, Bitboard(occupancy)).0,
+                    sliding_attacks(square, occupancy, &ROOK_DIRECTIONS)
+                );
+                assert_eq!(
+                    bishop_attacks(sq, Bitboard(occupancy)).0,
+                    sliding_attacks(square, occupancy, &BISHOP_DIRECTIONS)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rook_attacks_with_blockers() {
+        let occupancy = Square::A4.bitboard() | Square::C1.bitboard();
+        let expected = Square::A2.bitboard()
+            | Square::A3.bitboard()
+            | Square::A4.bitboard()
+            | Square::B1.bitboard()
+            | Square::C1.bitboard();
+
+        assert_eq!(rook_attacks(Square::A1, occupancy), expected);
+    }
+}
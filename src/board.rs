@@ -1,13 +1,23 @@
-use crate::bitboard::Bitboard;
-use crate::castling_rights::{CastlingRight, CastlingRights};
+use crate::bitboard::{
+    Bitboard, bishop_attacks, king_attacks, knight_attacks, pawn_attacks, rook_attacks,
+};
+use crate::castling_rights::CastlingRights;
 use crate::color::Color;
 use crate::color::Color::{Black, White};
+use crate::evaluation::{PhaseScore, phase_weight, piece_square_score};
 use crate::moves::{Move, MoveType};
 use crate::piece::Piece;
 use crate::square::Square;
+use crate::zobrist::ZOBRIST;
 use colored::Colorize;
 use std::fmt::{Display, Formatter};
 
+mod chess960;
+mod fen;
+mod movegen;
+
+pub use fen::FenError;
+
 pub const BOARD_SIZE: u8 = 8;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -20,6 +30,12 @@ pub struct Board {
     castling_rights: CastlingRights,
     half_moves: usize,
     full_moves: usize,
+    hash: u64,
+    /// Zobrist key of the pawns only, see [Board::pawn_hash]
+    pawn_hash: u64,
+    /// Material and piece-square tables from white's point of view, updated with every piece
+    psqt: PhaseScore,
+    phase: i32,
 }
 
 impl Board {
@@ -27,128 +43,141 @@ impl Board {
         Self::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap()
     }
 
-    pub fn from_fen(fen: &str) -> Result<Self, ()> {
-        let mut parts = fen.split(" ");
-        let fen_piece_placement = parts.next().unwrap();
-        let fen_active_color = parts.next().unwrap();
-        let fen_castling_rights = parts.next().unwrap();
-        let fen_en_passant = parts.next().unwrap();
-        let fen_halfmoves = parts.next().unwrap_or("0");
-        let fen_fullmoves = parts.next().unwrap_or("1");
-
-        let mut board = Board::default();
-
-        let mut index = 0u8;
-        for char in fen_piece_placement.chars() {
-            // FEN starts at the top left (a8). My internal representation has a1 as 0 => We have to
-            // invert the rank with 7 - rank to start adding piece at the board from the bottom.
-            let rank = index / BOARD_SIZE;
-            let file = index % BOARD_SIZE;
-            let square = Square::from_file_and_rank(file, 7 - rank);
-            match char {
-                '/' => {
-                    index -= 1; // Index is always incremented after match -> decrement here to leave it at the same value
-                }
-                num if num.is_ascii_digit() => {
-                    let num = char::to_digit(num, 10).unwrap() as u8;
-                    index += num - 1;
-                }
-                x => {
-                    if let Some(piece) = Piece::from_char(x) {
-                        board.add_piece(piece, square);
-                    } else {
-                        return Err(());
-                    }
-                }
-            }
-            index += 1;
-        }
+    pub fn opponent(&self) -> Color {
+        self.current_color.other()
+    }
 
-        board.current_color = match fen_active_color {
-            "w" => White,
-            "b" => Black,
-            _ => return Err(()),
-        };
+    pub fn side_to_move(&self) -> Color {
+        self.current_color
+    }
 
-        for char in fen_castling_rights.chars() {
-            match char {
-                'K' => board.castling_rights.add(CastlingRight::WhiteKingside),
-                'k' => board.castling_rights.add(CastlingRight::BlackKingside),
-                'Q' => board.castling_rights.add(CastlingRight::WhiteQueenside),
-                'q' => board.castling_rights.add(CastlingRight::BlackQueenside),
-                '-' => {}
-                x => println!("Unexpected char {x} in castling rights"),
-            }
-        }
+    pub fn piece_on(&self, square: Square) -> Piece {
+        self.pieces[square]
+    }
 
-        board.half_moves = fen_halfmoves.parse().unwrap_or_default();
-        board.full_moves = fen_fullmoves.parse().unwrap_or_default();
-        board.en_passant_square = Square::from_algebraic_notation(fen_en_passant);
+    pub fn pieces(&self, piece: Piece) -> Bitboard {
+        self.piece_bitboards[piece]
+    }
 
-        Ok(board)
+    pub fn colors(&self, color: Color) -> Bitboard {
+        self.color_bitboards[color]
     }
 
-    pub fn fen(&self) -> String {
-        let mut fen = String::new();
+    pub fn occupancy(&self) -> Bitboard {
+        self.color_bitboards[White] | self.color_bitboards[Black]
+    }
 
-        for rank in (0..8).rev() {
-            let mut empty = 0;
+    pub fn en_passant_square(&self) -> Square {
+        self.en_passant_square
+    }
 
-            for file in 0..8 {
-                let square = Square::from_file_and_rank(file, rank);
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
 
-                let piece_char = self.pieces[square].char();
+    /// Plies since the last capture or pawn move, for the fifty-move rule
+    pub fn half_moves(&self) -> usize {
+        self.half_moves
+    }
 
-                match piece_char {
-                    Some(c) => {
-                        if empty > 0 {
-                            fen.push_str(&empty.to_string());
-                            empty = 0;
-                        }
-                        fen.push(c);
-                    }
-                    None => empty += 1,
-                }
-            }
+    /// Number of the current move, starting at 1 and incremented after every black move
+    pub fn full_moves(&self) -> usize {
+        self.full_moves
+    }
 
-            if empty > 0 {
-                fen.push_str(&empty.to_string());
-            }
+    pub fn king_square(&self, color: Color) -> Square {
+        self.pieces(Piece::WhiteKing.with_color(color)).lsb()
+    }
 
-            if rank > 0 {
-                fen.push('/');
-            }
-        }
+    /// Returns the pieces of both colors attacking `square`, with sliders seeing through
+    /// `occupancy` instead of the actual board.
+    pub fn attackers_to(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+        let knights = self.pieces(Piece::WhiteKnight) | self.pieces(Piece::BlackKnight);
+        let kings = self.pieces(Piece::WhiteKing) | self.pieces(Piece::BlackKing);
+        let queens = self.pieces(Piece::WhiteQueen) | self.pieces(Piece::BlackQueen);
+        let diagonal = self.pieces(Piece::WhiteBishop) | self.pieces(Piece::BlackBishop) | queens;
+        let orthogonal = self.pieces(Piece::WhiteRook) | self.pieces(Piece::BlackRook) | queens;
+
+        (pawn_attacks(square, Black) & self.pieces(Piece::WhitePawn))
+            | (pawn_attacks(square, White) & self.pieces(Piece::BlackPawn))
+            | (knight_attacks(square) & knights)
+            | (king_attacks(square) & kings)
+            | (bishop_attacks(square, occupancy) & diagonal)
+            | (rook_attacks(square, occupancy) & orthogonal)
+    }
 
-        // Active color
-        fen.push(' ');
-        fen.push(self.current_color.char());
+    pub fn is_square_attacked(&self, square: Square, by: Color) -> bool {
+        (self.attackers_to(square, self.occupancy()) & self.colors(by)).is_not_empty()
+    }
 
-        // Castling rights
-        fen.push(' ');
-        fen.push_str(self.castling_rights.to_string().as_str());
+    pub fn in_check(&self) -> bool {
+        self.is_square_attacked(self.king_square(self.current_color), self.opponent())
+    }
 
-        // En passant
-        fen.push(' ');
-        if self.en_passant_square != Square::None {
-            fen.push_str(format!("{}", self.en_passant_square).as_str());
-        } else {
-            fen.push('-');
+    /// Zobrist key of the position
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Zobrist key of the pawns alone, used to cache the pawn structure evaluation. Positions
+    /// without pawns have the key 0.
+    pub fn pawn_hash(&self) -> u64 {
+        self.pawn_hash
+    }
+
+    /// Material and piece-square score from white's point of view, before tapering
+    pub fn piece_square_score(&self) -> PhaseScore {
+        self.psqt
+    }
+
+    /// Remaining non-pawn material, [MAX_PHASE](crate::evaluation::MAX_PHASE) in the starting
+    /// position and 0 with only kings and pawns left
+    pub fn phase(&self) -> i32 {
+        self.phase
+    }
+
+    /// Computes the Zobrist key from scratch. The en passant square is only hashed if a pawn
+    /// can actually capture there, so positions that only differ by an unusable en passant
+    /// square share a key.
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = ZOBRIST.side(self.current_color) ^ ZOBRIST.castling(self.castling_rights);
+
+        for square in self.occupancy() {
+            hash ^= ZOBRIST.piece(self.pieces[square], square);
         }
 
-        fen.push(' ');
-        fen.push_str(&self.half_moves.to_string());
-        fen.push(' ');
-        fen.push_str(&self.full_moves.to_string());
+        hash ^ self.en_passant_key()
+    }
 
-        fen
+    /// Computes the pawn key from scratch, see [Board::pawn_hash].
+    pub fn compute_pawn_hash(&self) -> u64 {
+        let pawns = self.pieces(Piece::WhitePawn) | self.pieces(Piece::BlackPawn);
+        pawns.into_iter().fold(0, |hash, square| {
+            hash ^ ZOBRIST.piece(self.pieces[square], square)
+        })
     }
 
-    pub fn opponent(&self) -> Color {
-        self.current_color.other()
+    /// Key of the en passant square, or 0 if the side to move cannot capture en passant.
+    fn en_passant_key(&self) -> u64 {
+        if self.en_passant_square == Square::None {
+            return 0;
+        }
+
+        let pawns = self.pieces(Piece::WhitePawn.with_color(self.current_color));
+        if (pawn_attacks(self.en_passant_square, self.opponent()) & pawns).is_empty() {
+            return 0;
+        }
+
+        ZOBRIST.en_passant(self.en_passant_square)
     }
 
     pub fn add_piece(&mut self, piece: Piece, square: Square) {
+        self.hash ^= ZOBRIST.piece(piece, square);
+        if piece.with_color(White) == Piece::WhitePawn {
+            self.pawn_hash ^= ZOBRIST.piece(piece, square);
+        }
+        self.psqt += piece_square_score(piece, square);
+        self.phase += phase_weight(piece);
         self.piece_bitboards[piece].add(square);
         self.color_bitboards[piece.get_color()].add(square);
         self.pieces[square] = piece;
@@ -165,12 +194,29 @@ impl Board {
             piece.get_color()
         );
 
+        self.hash ^= ZOBRIST.piece(piece, square);
+        if piece.with_color(White) == Piece::WhitePawn {
+            self.pawn_hash ^= ZOBRIST.piece(piece, square);
+        }
+        self.psqt -= piece_square_score(piece, square);
+        self.phase -= phase_weight(piece);
         self.piece_bitboards[piece].remove(square);
         self.color_bitboards[piece.get_color()].remove(square);
         self.pieces[square] = Piece::None;
     }
 
-    pub fn make_move(&mut self, mv: Move) {
+    /// Returns the piece `mv` captures, or [Piece::None] if it is not a capture.
+    pub fn captured_piece(&self, mv: Move) -> Piece {
+        match mv.ty() {
+            MoveType::EnPassant => Piece::WhitePawn.with_color(self.opponent()),
+            _ if mv.is_capture() => self.pieces[mv.to()],
+            _ => Piece::None,
+        }
+    }
+
+    /// Plays `mv` and returns the [Undo] record needed to take it back with
+    /// [Board::unmake_move].
+    pub fn make_move(&mut self, mv: Move) -> Undo {
         let moving_piece = self.pieces[mv.from()];
         debug_assert_ne!(
             moving_piece,
@@ -179,82 +225,154 @@ impl Board {
             mv.from()
         );
 
-        // Normal moves, double pushes, captures, and promotions
-        self.remove_piece(moving_piece, mv.from());
-        let added_piece = mv.get_promotion().unwrap_or(moving_piece);
-        self.add_piece(added_piece, mv.to());
+        let undo = Undo {
+            captured: self.captured_piece(mv),
+            castling_rights: self.castling_rights.raw(),
+            en_passant_square: self.en_passant_square,
+            half_moves: self.half_moves,
+            hash: self.hash,
+        };
 
-        // Special cases
-        match mv.ty() {
-            MoveType::EnPassant => {
+        self.hash ^= self.en_passant_key() ^ ZOBRIST.castling(self.castling_rights);
+        self.en_passant_square = Square::None;
+
+        // Captures, including en passant and promotion captures
+        if mv.is_capture() {
+            debug_assert!(
+                undo.captured != Piece::None && undo.captured.get_color() == self.opponent(),
+                "Move {mv} captures no enemy piece"
+            );
+            let capture_square = if mv.ty() == MoveType::EnPassant {
                 debug_assert_eq!(
-                    self.en_passant_square,
+                    undo.en_passant_square,
                     mv.to(),
                     "Square {} not marked as en-passant square",
                     mv.to()
                 );
-                let captured_pawn = Piece::WhitePawn.with_color(self.opponent());
-                self.remove_piece(captured_pawn, mv.to() ^ 8); // Evil magic bit trick stolen from the Reckless engine.
-            }
-            MoveType::DoublePush => {
-                self.en_passant_square = Square::new((mv.from() as u8 + mv.to() as u8) / 2);
-            }
-            MoveType::Castling => {
-                let (rook_from, rook_to) = mv.get_rook_from_to_square_castling();
-                let rook = Piece::WhiteRook.with_color(self.current_color);
+                mv.to() ^ 8 // Evil magic bit trick stolen from the Reckless engine.
+            } else {
+                mv.to()
+            };
+            self.remove_piece(undo.captured, capture_square);
+        }
+
+        if mv.ty() == MoveType::Castling {
+            // In Chess960 king and rook may land on each other's origin, so both are lifted
+            // before either is placed
+            let (king_to, rook_to) = mv.castling_destinations();
+            let rook = Piece::WhiteRook.with_color(self.current_color);
+
+            self.remove_piece(moving_piece, mv.from());
+            self.remove_piece(rook, mv.to());
+            self.add_piece(moving_piece, king_to);
+            self.add_piece(rook, rook_to);
+        } else {
+            // Normal moves, double pushes, captures, and promotions
+            self.remove_piece(moving_piece, mv.from());
+            let added_piece = mv
+                .get_promotion()
+                .map_or(moving_piece, |piece| piece.with_color(self.current_color));
+            self.add_piece(added_piece, mv.to());
+        }
 
-                self.remove_piece(rook, rook_from);
-                self.add_piece(rook, rook_to);
+        if mv.ty() == MoveType::DoublePush {
+            // Like Stockfish, only remember the en passant square if an enemy pawn could
+            // actually capture there
+            let en_passant_square = Square::new((mv.from() as u8 + mv.to() as u8) / 2);
+            let enemy_pawns = self.pieces(Piece::WhitePawn.with_color(self.opponent()));
+            if (pawn_attacks(en_passant_square, self.current_color) & enemy_pawns).is_not_empty() {
+                self.en_passant_square = en_passant_square;
             }
-            _ => {}
         }
 
-        self.update_castling_rights(mv);
+        // The fifty-move counter is reset by captures and pawn moves
+        if mv.is_capture() || moving_piece.with_color(White) == Piece::WhitePawn {
+            self.half_moves = 0;
+        } else {
+            self.half_moves += 1;
+        }
+
+        if self.current_color == Black {
+            self.full_moves += 1;
+        }
+
+        self.castling_rights.update(mv.from(), mv.to());
         self.current_color = self.opponent();
+
+        self.hash ^=
+            ZOBRIST.castling(self.castling_rights) ^ ZOBRIST.side(Black) ^ self.en_passant_key();
+        debug_assert_eq!(
+            self.hash,
+            self.compute_hash(),
+            "Incremental hash diverged after {mv}"
+        );
+        debug_assert_eq!(
+            self.pawn_hash,
+            self.compute_pawn_hash(),
+            "Incremental pawn hash diverged after {mv}"
+        );
+
+        undo
     }
 
-    fn update_castling_rights(&mut self, mv: Move) {
-        // Rook or King get moved
-        match mv.from() {
-            Square::E1 => {
-                self.castling_rights.remove(CastlingRight::WhiteKingside);
-                self.castling_rights.remove(CastlingRight::WhiteQueenside);
-            }
-            Square::H1 => {
-                self.castling_rights.remove(CastlingRight::WhiteKingside);
-            }
-            Square::A1 => {
-                self.castling_rights.remove(CastlingRight::WhiteQueenside);
-            }
-            Square::E8 => {
-                self.castling_rights.remove(CastlingRight::BlackKingside);
-                self.castling_rights.remove(CastlingRight::BlackQueenside);
-            }
-            Square::H8 => {
-                self.castling_rights.remove(CastlingRight::BlackKingside);
-            }
-            Square::A8 => {
-                self.castling_rights.remove(CastlingRight::BlackQueenside);
-            }
-            _ => {}
-        };
+    /// Takes back `mv`, which must be the last move played with [Board::make_move] that
+    /// returned `undo`. Restores the exact prior board.
+    pub fn unmake_move(&mut self, mv: Move, undo: Undo) {
+        self.current_color = self.opponent();
 
-        // Rook gets captured
-        match mv.to() {
-            Square::H1 => {
-                self.castling_rights.remove(CastlingRight::WhiteKingside);
-            }
-            Square::A1 => {
-                self.castling_rights.remove(CastlingRight::WhiteQueenside);
-            }
-            Square::H8 => {
-                self.castling_rights.remove(CastlingRight::BlackKingside);
-            }
-            Square::A8 => {
-                self.castling_rights.remove(CastlingRight::BlackQueenside);
+        if mv.ty() == MoveType::Castling {
+            let (king_to, rook_to) = mv.castling_destinations();
+            let king = Piece::WhiteKing.with_color(self.current_color);
+            let rook = Piece::WhiteRook.with_color(self.current_color);
+
+            self.remove_piece(king, king_to);
+            self.remove_piece(rook, rook_to);
+            self.add_piece(king, mv.from());
+            self.add_piece(rook, mv.to());
+        } else {
+            let moved_piece = self.pieces[mv.to()];
+            let original_piece = if mv.is_promotion() {
+                Piece::WhitePawn.with_color(self.current_color)
+            } else {
+                moved_piece
+            };
+
+            self.remove_piece(moved_piece, mv.to());
+            self.add_piece(original_piece, mv.from());
+
+            match mv.ty() {
+                MoveType::EnPassant => self.add_piece(undo.captured, mv.to() ^ 8),
+                _ if mv.is_capture() => self.add_piece(undo.captured, mv.to()),
+                _ => {}
             }
-            _ => {}
-        };
+        }
+
+        self.castling_rights.restore(undo.castling_rights);
+        self.en_passant_square = undo.en_passant_square;
+        self.half_moves = undo.half_moves;
+        self.hash = undo.hash;
+        if self.current_color == Black {
+            self.full_moves -= 1;
+        }
+    }
+}
+
+/// State lost by [Board::make_move] that [Board::unmake_move] needs to restore the previous
+/// position.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Undo {
+    captured: Piece,
+    /// See [CastlingRights::raw], the home squares never change during a game
+    castling_rights: u8,
+    en_passant_square: Square,
+    half_moves: usize,
+    hash: u64,
+}
+
+impl Undo {
+    /// The piece captured by the move, or [Piece::None] if it was not a capture.
+    pub fn captured(&self) -> Piece {
+        self.captured
     }
 }
 
@@ -269,6 +387,10 @@ impl Default for Board {
             castling_rights: Default::default(),
             half_moves: 0,
             full_moves: 1,
+            hash: 0,
+            pawn_hash: 0,
+            psqt: PhaseScore::default(),
+            phase: 0,
         }
     }
 }
@@ -330,4 +452,173 @@ mod tests {
 
         assert_eq!(board.castling_rights, CastlingRights::all());
     }
+
+    /// Asserts that the mailbox and all bitboards describe the same position.
+    fn assert_consistent(board: &Board) {
+        for index in 0..64 {
+            let square = Square::new(index);
+            let piece = board.pieces[square];
+
+            for (i, bitboard) in board.piece_bitboards.iter().enumerate() {
+                assert_eq!(bitboard.has(square), piece as usize == i, "{board}");
+            }
+            for color in [White, Black] {
+                let expected = piece != Piece::None && piece.get_color() == color;
+                assert_eq!(board.colors(color).has(square), expected, "{board}");
+            }
+        }
+    }
+
+    #[test]
+    fn capture_removes_victim() {
+        let mut board = Board::from_fen("4k3/8/8/3p4/4N3/8/8/4K3 w - - 0 1").unwrap();
+        let undo = board.make_move(Move::new(Square::E4, Square::D5, MoveType::Capture));
+
+        assert_eq!(undo.captured(), Piece::BlackPawn);
+        assert!(board.pieces(Piece::BlackPawn).is_empty());
+        assert_eq!(board.colors(Black), Square::E8.bitboard());
+        assert_consistent(&board);
+    }
+
+    #[test]
+    fn promotion_capture_removes_victim() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/6p1/4K2R b - - 0 1").unwrap();
+        let mv = Move::new(Square::G2, Square::H1, MoveType::PromotionCaptureKnight);
+        let undo = board.make_move(mv);
+
+        assert_eq!(undo.captured(), Piece::WhiteRook);
+        assert_eq!(board.piece_on(Square::H1), Piece::BlackKnight);
+        assert!(board.pieces(Piece::WhiteRook).is_empty());
+        assert!(board.pieces(Piece::BlackPawn).is_empty());
+        assert_eq!(board.colors(White), Square::E1.bitboard());
+        assert_consistent(&board);
+    }
+
+    #[test]
+    fn en_passant_removes_victim() {
+        let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        let undo = board.make_move(Move::new(Square::E5, Square::D6, MoveType::EnPassant));
+
+        assert_eq!(undo.captured(), Piece::BlackPawn);
+        assert!(board.pieces(Piece::BlackPawn).is_empty());
+        assert_eq!(board.piece_on(Square::D6), Piece::WhitePawn);
+        assert_consistent(&board);
+    }
+
+    #[test]
+    fn make_move_updates_fen_counters() {
+        let mut board = Board::from_fen("4k3/4p3/8/3P4/8/8/8/4K3 b - - 0 1").unwrap();
+
+        board.make_move(Move::new(Square::E7, Square::E5, MoveType::DoublePush));
+        assert_eq!(board.fen(), "4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 2");
+
+        board.make_move(Move::new(Square::E1, Square::E2, MoveType::Normal));
+        assert_eq!(board.fen(), "4k3/8/8/3Pp3/8/8/4K3/8 b - - 1 2");
+
+        board.make_move(Move::new(Square::E8, Square::D7, MoveType::Normal));
+        assert_eq!(board.fen(), "8/3k4/8/3Pp3/8/8/4K3/8 w - - 2 3");
+
+        board.make_move(Move::new(Square::E2, Square::E3, MoveType::Normal));
+        board.make_move(Move::new(Square::D7, Square::D6, MoveType::Normal));
+        assert_eq!(board.fen(), "8/8/3k4/3Pp3/8/4K3/8/8 w - - 4 4");
+
+        board.make_move(Move::new(Square::E3, Square::F3, MoveType::Normal));
+        board.make_move(Move::new(Square::D6, Square::D5, MoveType::Capture));
+        assert_eq!(board.fen(), "8/8/8/3kp3/8/5K2/8/8 w - - 0 5");
+    }
+
+    #[test]
+    fn double_push_without_attacker_sets_no_en_passant() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        board.make_move(Move::new(Square::E2, Square::E4, MoveType::DoublePush));
+
+        assert_eq!(board.fen(), "4k3/8/8/8/4P3/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn hash_transpositions() {
+        let start = Board::starting_position();
+        let mut board = start;
+
+        for (from, to) in [
+            (Square::G1, Square::F3),
+            (Square::G8, Square::F6),
+            (Square::F3, Square::G1),
+            (Square::F6, Square::G8),
+        ] {
+            board.make_move(Move::new(from, to, MoveType::Normal));
+        }
+        assert_eq!(board.hash(), start.hash());
+        // Knight moves leave the pawns alone
+        assert_eq!(board.pawn_hash(), start.pawn_hash());
+
+        let mut a = Board::starting_position();
+        a.make_move(Move::new(Square::E2, Square::E3, MoveType::Normal));
+        a.make_move(Move::new(Square::E7, Square::E6, MoveType::Normal));
+        a.make_move(Move::new(Square::D2, Square::D3, MoveType::Normal));
+
+        let mut b = Board::starting_position();
+        b.make_move(Move::new(Square::D2, Square::D3, MoveType::Normal));
+        b.make_move(Move::new(Square::E7, Square::E6, MoveType::Normal));
+        b.make_move(Move::new(Square::E2, Square::E3, MoveType::Normal));
+
+        assert_eq!(a.hash(), b.hash());
+        assert_ne!(a.hash(), start.hash());
+        assert_eq!(a.pawn_hash(), b.pawn_hash());
+        assert_ne!(a.pawn_hash(), start.pawn_hash());
+    }
+
+    #[test]
+    fn hash_ignores_unusable_en_passant_square() {
+        let with = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+        let without = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(with.hash(), without.hash());
+
+        let with = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        let without = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap();
+        assert_ne!(with.hash(), without.hash());
+    }
+
+    #[test]
+    fn make_unmake_round_trip() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        ];
+
+        // Simple xorshift to get reproducible games
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for fen in fens {
+            for _ in 0..400 {
+                let mut board = Board::from_fen(fen).unwrap();
+                let mut history = Vec::new();
+
+                for _ in 0..40 {
+                    let moves = board.legal_moves();
+                    if moves.is_empty() {
+                        break;
+                    }
+                    let mv = moves[random() as usize % moves.len()];
+                    history.push((board, board.fen(), mv, board.make_move(mv)));
+                    assert_consistent(&board);
+                }
+
+                while let Some((before, fen, mv, undo)) = history.pop() {
+                    board.unmake_move(mv, undo);
+                    assert_eq!(board.fen(), fen);
+                    assert_eq!(board, before);
+                }
+            }
+        }
+    }
 }
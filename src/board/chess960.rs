@@ -0,0 +1,80 @@
+use crate::board::Board;
+
+/// Placements of the two knights on the five squares left after the bishops and the queen
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+impl Board {
+    /// Start position number `index` (0 to 959) of [Chess960](https://www.chessprogramming.org/Chess960),
+    /// using the standard numbering where 518 is the classical starting position. Returns
+    /// [None] for larger indices.
+    pub fn chess960(index: usize) -> Option<Board> {
+        if index >= 960 {
+            return None;
+        }
+
+        let mut back_rank = [None; 8];
+        let (n, light_bishop) = (index / 4, index % 4);
+        let (n, dark_bishop) = (n / 4, n % 4);
+        let (n, queen) = (n / 6, n % 6);
+        back_rank[2 * light_bishop + 1] = Some('B');
+        back_rank[2 * dark_bishop] = Some('B');
+
+        let mut place_on_empty = |nth: usize, piece: char| {
+            let file = (0..8).filter(|&file| back_rank[file].is_none()).nth(nth);
+            back_rank[file.unwrap()] = Some(piece);
+        };
+        let (first_knight, second_knight) = KNIGHTS[n];
+        place_on_empty(queen, 'Q');
+        // The second knight comes first, so placing it does not shift the first one
+        place_on_empty(second_knight, 'N');
+        place_on_empty(first_knight, 'N');
+        place_on_empty(0, 'R');
+        place_on_empty(0, 'K');
+        place_on_empty(0, 'R');
+
+        let white: String = back_rank.iter().map(|piece| piece.unwrap()).collect();
+        let black = white.to_ascii_lowercase();
+        let fen = format!("{black}/pppppppp/8/8/8/8/PPPPPPPP/{white} w KQkq - 0 1");
+        Some(Board::from_fen_chess960(&fen).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_positions() {
+        let fen = |index| Board::chess960(index).unwrap().fen();
+
+        assert_eq!(fen(518), Board::starting_position().fen());
+        assert_eq!(
+            fen(0),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+        );
+        assert_eq!(
+            fen(959),
+            "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1"
+        );
+        assert_eq!(Board::chess960(960), None);
+    }
+
+    #[test]
+    fn shredder_fen_round_trip() {
+        for index in 0..960 {
+            let board = Board::chess960(index).unwrap();
+            assert_eq!(Board::from_fen(&board.shredder_fen()), Ok(board), "{index}");
+        }
+    }
+}
@@ -0,0 +1,492 @@
+use crate::bitboard::Bitboard;
+use crate::board::{BOARD_SIZE, Board};
+use crate::castling_rights::CastlingRight;
+use crate::color::Color::{self, Black, White};
+use crate::piece::Piece;
+use crate::square::Square;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Why a FEN string could not be turned into a [Board]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FenError {
+    /// Fewer than the four mandatory fields
+    MissingField(&'static str),
+    TooManyFields,
+    /// Piece placement does not consist of eight ranks
+    BadRankCount(usize),
+    /// A rank (1 to 8) does not describe exactly eight squares
+    BadRankLength(u8),
+    UnknownPiece(char),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidHalfMoves(String),
+    InvalidFullMoves(String),
+    /// Not exactly one king of the given color
+    KingCount(Color),
+    PawnOnBackRank(Square),
+    /// The side that just moved left its king in check
+    OpponentInCheck,
+    /// A castling right is set, but the king or rook is not on its home square
+    CastlingWithoutPieces(CastlingRight),
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FenError::MissingField(field) => write!(f, "missing {field}"),
+            FenError::TooManyFields => write!(f, "more than six fields"),
+            FenError::BadRankCount(count) => write!(f, "expected 8 ranks, found {count}"),
+            FenError::BadRankLength(rank) => write!(f, "rank {rank} does not have 8 squares"),
+            FenError::UnknownPiece(c) => write!(f, "unknown piece '{c}'"),
+            FenError::InvalidSideToMove(side) => write!(f, "invalid side to move '{side}'"),
+            FenError::InvalidCastling(castling) => {
+                write!(f, "invalid castling rights '{castling}'")
+            }
+            FenError::InvalidEnPassant(square) => {
+                write!(f, "invalid en passant square '{square}'")
+            }
+            FenError::InvalidHalfMoves(half_moves) => {
+                write!(f, "invalid halfmove clock '{half_moves}'")
+            }
+            FenError::InvalidFullMoves(full_moves) => {
+                write!(f, "invalid fullmove number '{full_moves}'")
+            }
+            FenError::KingCount(color) => write!(f, "{color} does not have exactly one king"),
+            FenError::PawnOnBackRank(square) => write!(f, "pawn on back rank square {square}"),
+            FenError::OpponentInCheck => write!(f, "the side not to move is in check"),
+            FenError::CastlingWithoutPieces(right) => write!(
+                f,
+                "castling right {} without king and rook on their home squares",
+                right.char()
+            ),
+        }
+    }
+}
+
+impl Error for FenError {}
+
+impl Board {
+    /// Parses a position in [Forsyth-Edwards Notation](https://www.chessprogramming.org/Forsyth-Edwards_Notation).
+    /// The move counters may be omitted and default to `0 1`.
+    ///
+    /// Besides the syntax, the position itself is validated, so every returned [Board] is safe
+    /// to generate moves for.
+    ///
+    /// Castling rights written as files (Shredder-FEN) are read as
+    /// [Chess960](https://www.chessprogramming.org/Chess960). Otherwise they require the king on
+    /// e1/e8 and the rooks in the corners, see [Board::from_fen_chess960] for X-FEN.
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        Self::parse_fen(fen, false)
+    }
+
+    /// Like [Board::from_fen], but also reads `KQkq` as in
+    /// [X-FEN](https://en.wikipedia.org/wiki/X-FEN): the outermost rook on that side of a king
+    /// standing anywhere on the back rank.
+    pub fn from_fen_chess960(fen: &str) -> Result<Self, FenError> {
+        Self::parse_fen(fen, true)
+    }
+
+    fn parse_fen(fen: &str, chess960: bool) -> Result<Self, FenError> {
+        let mut fields = fen.split_whitespace();
+        let piece_placement = fields
+            .next()
+            .ok_or(FenError::MissingField("piece placement"))?;
+        let active_color = fields
+            .next()
+            .ok_or(FenError::MissingField("side to move"))?;
+        let castling_rights = fields
+            .next()
+            .ok_or(FenError::MissingField("castling rights"))?;
+        let en_passant = fields
+            .next()
+            .ok_or(FenError::MissingField("en passant square"))?;
+        let half_moves = fields.next().unwrap_or("0");
+        let full_moves = fields.next().unwrap_or("1");
+        if fields.next().is_some() {
+            return Err(FenError::TooManyFields);
+        }
+
+        let mut board = Board::default();
+
+        let ranks: Vec<&str> = piece_placement.split('/').collect();
+        if ranks.len() != BOARD_SIZE as usize {
+            return Err(FenError::BadRankCount(ranks.len()));
+        }
+
+        // FEN starts at the top left (a8). My internal representation has a1 as 0 => We have to
+        // invert the rank with 7 - rank to start adding piece at the board from the bottom.
+        for (i, rank_text) in ranks.iter().enumerate() {
+            let rank = BOARD_SIZE - 1 - i as u8;
+            let mut file = 0;
+
+            for c in rank_text.chars() {
+                if let Some(empty) = c.to_digit(10) {
+                    if !(1..=8).contains(&empty) {
+                        return Err(FenError::BadRankLength(rank + 1));
+                    }
+                    file += empty as u8;
+                } else {
+                    let piece = Piece::try_from(c).map_err(|_| FenError::UnknownPiece(c))?;
+                    if file >= BOARD_SIZE {
+                        return Err(FenError::BadRankLength(rank + 1));
+                    }
+                    board.add_piece(piece, Square::from_file_and_rank(file, rank));
+                    file += 1;
+                }
+
+                if file > BOARD_SIZE {
+                    return Err(FenError::BadRankLength(rank + 1));
+                }
+            }
+
+            if file != BOARD_SIZE {
+                return Err(FenError::BadRankLength(rank + 1));
+            }
+        }
+
+        board.current_color = active_color
+            .parse()
+            .map_err(|_| FenError::InvalidSideToMove(active_color.to_string()))?;
+
+        if castling_rights != "-" {
+            // Rook files are only used in Chess960
+            let chess960 = chess960
+                || castling_rights
+                    .chars()
+                    .any(|c| matches!(c.to_ascii_lowercase(), 'a'..='h'));
+            for c in castling_rights.chars() {
+                let invalid = || FenError::InvalidCastling(castling_rights.to_string());
+                let (color, back_rank) = if c.is_ascii_uppercase() {
+                    (White, 0)
+                } else {
+                    (Black, BOARD_SIZE - 1)
+                };
+                let rank_mask = Bitboard::RANK_1 << (back_rank as i32 * 8);
+                let king = (board.pieces(Piece::WhiteKing.with_color(color)) & rank_mask).lsb();
+                let rooks = board.pieces(Piece::WhiteRook.with_color(color)) & rank_mask;
+
+                let (right, rook) = match c.to_ascii_lowercase() {
+                    // X-FEN: the outermost rook on that side of the king
+                    side @ ('k' | 'q') => {
+                        let kingside = side == 'k';
+                        let right = CastlingRight::new(color, kingside);
+                        let mut candidates = rooks.into_iter().filter(|rook| {
+                            king != Square::None && (rook.file() > king.file()) == kingside
+                        });
+                        let rook = if kingside {
+                            candidates.last()
+                        } else {
+                            candidates.next()
+                        };
+                        (right, rook.ok_or(FenError::CastlingWithoutPieces(right))?)
+                    }
+                    // Shredder-FEN: the file of the rook
+                    file @ 'a'..='h' => {
+                        if king == Square::None {
+                            return Err(invalid());
+                        }
+                        let rook = Square::from_file_and_rank(file as u8 - b'a', back_rank);
+                        let right = CastlingRight::new(color, rook.file() > king.file());
+                        if !rooks.has(rook) {
+                            return Err(FenError::CastlingWithoutPieces(right));
+                        }
+                        (right, rook)
+                    }
+                    _ => return Err(invalid()),
+                };
+
+                if board.castling_rights.can_castle(right) {
+                    return Err(invalid());
+                }
+                board.castling_rights.add(right, king, rook);
+                if !chess960 && !board.castling_rights.is_standard() {
+                    return Err(FenError::CastlingWithoutPieces(right));
+                }
+            }
+        }
+
+        if en_passant != "-" {
+            board.en_passant_square = en_passant
+                .parse()
+                .map_err(|_| FenError::InvalidEnPassant(en_passant.to_string()))?;
+        }
+
+        board.half_moves = half_moves
+            .parse()
+            .map_err(|_| FenError::InvalidHalfMoves(half_moves.to_string()))?;
+        // The fullmove number starts at 1
+        board.full_moves = full_moves
+            .parse()
+            .ok()
+            .filter(|&full_moves| full_moves > 0)
+            .ok_or_else(|| FenError::InvalidFullMoves(full_moves.to_string()))?;
+
+        board.validate()?;
+        board.hash = board.compute_hash();
+
+        Ok(board)
+    }
+
+    /// Formats the position as FEN. Castling rights are written as in
+    /// [X-FEN](https://en.wikipedia.org/wiki/X-FEN), which is plain FEN for standard chess and
+    /// only names the rook's file if it is not the outermost one.
+    pub fn fen(&self) -> String {
+        self.format_fen(false)
+    }
+
+    /// Like [Board::fen], but castling rights always name the rook's file as in Shredder-FEN,
+    /// e.g. `HAha` for the starting position.
+    pub fn shredder_fen(&self) -> String {
+        self.format_fen(true)
+    }
+
+    fn format_fen(&self, shredder: bool) -> String {
+        let mut fen = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+
+            for file in 0..8 {
+                let square = Square::from_file_and_rank(file, rank);
+
+                let piece_char = self.pieces[square].char();
+
+                match piece_char {
+                    Some(c) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(c);
+                    }
+                    None => empty += 1,
+                }
+            }
+
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        // Active color
+        fen.push(' ');
+        fen.push(self.current_color.char());
+
+        // Castling rights
+        fen.push(' ');
+        let rooks = [White, Black].map(|color| self.pieces(Piece::WhiteRook.with_color(color)));
+        self.castling_rights
+            .write_fen(&mut fen, rooks, shredder)
+            .unwrap();
+
+        // En passant
+        fen.push(' ');
+        if self.en_passant_square != Square::None {
+            fen.push_str(format!("{}", self.en_passant_square).as_str());
+        } else {
+            fen.push('-');
+        }
+
+        fen.push(' ');
+        fen.push_str(&self.half_moves.to_string());
+        fen.push(' ');
+        fen.push_str(&self.full_moves.to_string());
+
+        fen
+    }
+
+    /// Rejects positions that cannot arise in a game and would break move generation.
+    fn validate(&self) -> Result<(), FenError> {
+        for color in [White, Black] {
+            if self
+                .pieces(Piece::WhiteKing.with_color(color))
+                .0
+                .count_ones()
+                != 1
+            {
+                return Err(FenError::KingCount(color));
+            }
+        }
+
+        let pawns = self.pieces(Piece::WhitePawn) | self.pieces(Piece::BlackPawn);
+        let back_ranks = Bitboard::RANK_1 | Bitboard::RANK_8;
+        if let Some(square) = (pawns & back_ranks).into_iter().next() {
+            return Err(FenError::PawnOnBackRank(square));
+        }
+
+        if self.is_square_attacked(self.king_square(self.opponent()), self.current_color) {
+            return Err(FenError::OpponentInCheck);
+        }
+
+        if self.en_passant_square != Square::None {
+            // The pawn that just made a double push has to stand in front of the square, which
+            // has to be empty just like the square the pawn started from
+            let (rank, pushed) = match self.current_color {
+                White => (5, Piece::BlackPawn),
+                Black => (2, Piece::WhitePawn),
+            };
+            let square = self.en_passant_square;
+            if square.rank() != rank
+                || self.pieces[square.shift(-self.current_color.pawn_push_offset())] != pushed
+                || self.pieces[square] != Piece::None
+                || self.pieces[square.shift(self.current_color.pawn_push_offset())] != Piece::None
+            {
+                return Err(FenError::InvalidEnPassant(square.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Same as [Board::from_fen]
+impl FromStr for Board {
+    type Err = FenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Board::from_fen(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syntax_errors() {
+        let cases = [
+            ("", FenError::MissingField("piece placement")),
+            (
+                "8/8/8/8/8/8/8/8 w KQkq",
+                FenError::MissingField("en passant square"),
+            ),
+            ("8/8/8/8/8/8/8 w - - 0 1", FenError::BadRankCount(7)),
+            ("9/8/8/8/8/8/8/8 w - - 0 1", FenError::BadRankLength(8)),
+            ("8/8/8/8/8/8/8/4k3K w - - 0 1", FenError::BadRankLength(1)),
+            ("8/8/8/8/8/8/8/4k2 w - - 0 1", FenError::BadRankLength(1)),
+            ("8/8/8/8/8/8/8/4x3 w - - 0 1", FenError::UnknownPiece('x')),
+            (
+                "4k3/8/8/8/8/8/8/4K3 white - - 0 1",
+                FenError::InvalidSideToMove("white".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/R3K3 w QX - 0 1",
+                FenError::InvalidCastling("QX".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - e9 0 1",
+                FenError::InvalidEnPassant("e9".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - x 1",
+                FenError::InvalidHalfMoves("x".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - 0 -1",
+                FenError::InvalidFullMoves("-1".to_string()),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - 0 0",
+                FenError::InvalidFullMoves("0".to_string()),
+            ),
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1 x", FenError::TooManyFields),
+        ];
+
+        for (fen, error) in cases {
+            assert_eq!(Board::from_fen(fen), Err(error), "{fen}");
+        }
+    }
+
+    #[test]
+    fn illegal_positions() {
+        let cases = [
+            ("8/8/8/8/8/8/8/4K3 w - - 0 1", FenError::KingCount(Black)),
+            ("4k3/8/8/8/8/8/8/3KK3 w - - 0 1", FenError::KingCount(White)),
+            (
+                "4k2P/8/8/8/8/8/8/4K3 w - - 0 1",
+                FenError::PawnOnBackRank(Square::H8),
+            ),
+            ("4k2R/8/8/8/8/8/8/4K3 w - - 0 1", FenError::OpponentInCheck),
+            (
+                "4k3/8/8/8/8/8/8/4KR2 w Q - 0 1",
+                FenError::CastlingWithoutPieces(CastlingRight::WhiteQueenside),
+            ),
+            (
+                "4k3/8/8/3pP3/8/8/8/4K3 w - e6 0 1",
+                FenError::InvalidEnPassant("e6".to_string()),
+            ),
+            (
+                "4k3/3p4/8/3pP3/8/8/8/4K3 w - d6 0 1",
+                FenError::InvalidEnPassant("d6".to_string()),
+            ),
+            // Without Chess960 the king and rooks have to be on their standard squares
+            (
+                "4k3/8/8/8/8/8/8/3K3R w K - 0 1",
+                FenError::CastlingWithoutPieces(CastlingRight::WhiteKingside),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/R2K4 w Q - 0 1",
+                FenError::CastlingWithoutPieces(CastlingRight::WhiteQueenside),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4KR2 w K - 0 1",
+                FenError::CastlingWithoutPieces(CastlingRight::WhiteKingside),
+            ),
+            (
+                "r3k1r1/8/8/8/8/8/8/4K3 w k - 0 1",
+                FenError::CastlingWithoutPieces(CastlingRight::BlackKingside),
+            ),
+            // The king has to stand between its castling rooks
+            (
+                "4k3/8/8/8/8/8/8/K1R4R w HC - 0 1",
+                FenError::InvalidCastling("HC".to_string()),
+            ),
+        ];
+
+        for (fen, error) in cases {
+            assert_eq!(Board::from_fen(fen), Err(error), "{fen}");
+        }
+    }
+
+    #[test]
+    fn chess960_castling_rights() {
+        let board = Board::from_fen("rk2r3/8/8/8/8/8/8/RK2R2R w EQkq - 0 1").unwrap();
+        assert_eq!(board.fen(), "rk2r3/8/8/8/8/8/8/RK2R2R w EQkq - 0 1");
+        assert_eq!(
+            board.shredder_fen(),
+            "rk2r3/8/8/8/8/8/8/RK2R2R w EAea - 0 1"
+        );
+        assert_eq!(Board::from_fen(&board.shredder_fen()), Ok(board));
+
+        // X-FEN only in Chess960 mode
+        let fen = "4k3/8/8/8/8/8/8/R2K3R w KQ - 0 1";
+        assert!(Board::from_fen(fen).is_err());
+        let board = Board::from_fen_chess960(fen).unwrap();
+        assert_eq!(board.shredder_fen(), "4k3/8/8/8/8/8/8/R2K3R w HA - 0 1");
+        assert_eq!(
+            Board::from_fen_chess960("4k3/8/8/8/8/8/8/K6R w KQ - 0 1"),
+            Err(FenError::CastlingWithoutPieces(
+                CastlingRight::WhiteQueenside
+            ))
+        );
+
+        let standard = Board::starting_position();
+        assert_eq!(
+            standard.shredder_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1"
+        );
+    }
+
+    #[test]
+    fn optional_counters() {
+        let board: Board = "4k3/8/8/3pP3/8/8/8/4K3 w - d6".parse().unwrap();
+        assert_eq!(board.fen(), "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+    }
+}
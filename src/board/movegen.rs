@@ -0,0 +1,388 @@
+use crate::bitboard::{
+    Bitboard, between, bishop_attacks, king_attacks, knight_attacks, line, pawn_attacks,
+    queen_attacks, rook_attacks,
+};
+use crate::board::Board;
+use crate::color::Color;
+use crate::moves::{Move, MoveList, MoveType};
+use crate::piece::Piece;
+use crate::square::Square;
+
+impl Board {
+    /// Generates every pseudo-legal move for the side to move. Moves may leave the own king in
+    /// check, with the exception of castling, which is only generated if the king does not
+    /// start on, pass through or land on an attacked square.
+    pub fn pseudo_legal_moves(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        let targets = !self.colors(self.current_color);
+
+        self.generate_pawn_moves(&mut moves, targets);
+        self.generate_piece_moves(&mut moves, Piece::WhiteKnight, targets, |square, _| {
+            knight_attacks(square)
+        });
+        self.generate_piece_moves(&mut moves, Piece::WhiteBishop, targets, bishop_attacks);
+        self.generate_piece_moves(&mut moves, Piece::WhiteRook, targets, rook_attacks);
+        self.generate_piece_moves(&mut moves, Piece::WhiteQueen, targets, queen_attacks);
+        self.generate_piece_moves(&mut moves, Piece::WhiteKing, targets, |square, _| {
+            king_attacks(square)
+        });
+        self.generate_castling_moves(&mut moves);
+
+        moves
+    }
+
+    /// Generates every strictly legal move for the side to move.
+    ///
+    /// Instead of playing each move and testing for check, legality is derived from the pieces
+    /// giving check, the evasion mask (capture the checker or block its ray) and the pin rays
+    /// of absolutely pinned pieces. Only en passant, which removes two pieces from a line at
+    /// once, is verified by looking at the resulting occupancy.
+    pub fn legal_moves(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        let us = self.current_color;
+        let them = us.other();
+        let king = self.king_square(us);
+        let occupancy = self.occupancy();
+        let enemies = self.colors(them);
+        let checkers = self.attackers_to(king, occupancy) & enemies;
+
+        // The king must not stay on a line with a slider, so it is removed from the occupancy
+        let occupancy_without_king = occupancy ^ king.bitboard();
+        for to in king_attacks(king) & !self.colors(us) {
+            if (self.attackers_to(to, occupancy_without_king) & enemies).is_empty() {
+                let ty = if enemies.has(to) {
+                    MoveType::Capture
+                } else {
+                    MoveType::Normal
+                };
+                moves.push(Move::new(king, to, ty));
+            }
+        }
+
+        // In double check only the king can move
+        if checkers.pop_count() > 1 {
+            return moves;
+        }
+
+        let targets = if checkers.is_empty() {
+            self.generate_castling_moves(&mut moves);
+            !self.colors(us)
+        } else {
+            between(king, checkers.lsb()) | checkers
+        };
+
+        let mut candidates = MoveList::new();
+        self.generate_pawn_moves(&mut candidates, targets);
+        self.generate_piece_moves(&mut candidates, Piece::WhiteKnight, targets, |square, _| {
+            knight_attacks(square)
+        });
+        self.generate_piece_moves(&mut candidates, Piece::WhiteBishop, targets, bishop_attacks);
+        self.generate_piece_moves(&mut candidates, Piece::WhiteRook, targets, rook_attacks);
+        self.generate_piece_moves(&mut candidates, Piece::WhiteQueen, targets, queen_attacks);
+
+        let pinned = self.pinned(king);
+        for &mv in &candidates {
+            let legal = if mv.ty() == MoveType::EnPassant {
+                self.is_legal_en_passant(mv, king)
+            } else {
+                !pinned.has(mv.from()) || line(king, mv.from()).has(mv.to())
+            };
+
+            if legal {
+                moves.push(mv);
+            }
+        }
+
+        moves
+    }
+
+    /// Returns the pieces of the side to move that are absolutely pinned to `king`.
+    fn pinned(&self, king: Square) -> Bitboard {
+        let us = self.current_color;
+        let them = us.other();
+        let occupancy = self.occupancy();
+        let queens = self.pieces(Piece::WhiteQueen.with_color(them));
+        let empty = Bitboard::empty();
+
+        let snipers = (rook_attacks(king, empty)
+            & (self.pieces(Piece::WhiteRook.with_color(them)) | queens))
+            | (bishop_attacks(king, empty)
+                & (self.pieces(Piece::WhiteBishop.with_color(them)) | queens));
+
+        let mut pinned = Bitboard::empty();
+        for sniper in snipers {
+            let blockers = between(king, sniper) & occupancy;
+            if blockers.pop_count() == 1 {
+                pinned |= blockers & self.colors(us);
+            }
+        }
+
+        pinned
+    }
+
+    /// En passant removes the capturing and the captured pawn from their rank at the same
+    /// time, which can expose the king in ways pin detection does not catch. The resulting
+    /// occupancy is therefore checked directly.
+    fn is_legal_en_passant(&self, mv: Move, king: Square) -> bool {
+        let captured = mv.to() ^ 8;
+        let occupancy =
+            (self.occupancy() ^ mv.from().bitboard() ^ captured.bitboard()) | mv.to().bitboard();
+        let attackers = self.attackers_to(king, occupancy)
+            & self.colors(self.opponent())
+            & !captured.bitboard();
+
+        attackers.is_empty()
+    }
+
+    fn generate_pawn_moves(&self, moves: &mut MoveList, targets: Bitboard) {
+        let us = self.current_color;
+        let pawns = self.pieces(Piece::WhitePawn.with_color(us));
+        let empty = !self.occupancy();
+        let enemies = self.colors(us.other());
+        let offset = us.pawn_push_offset();
+
+        let (start_rank, promotion_rank) = match us {
+            Color::White => (Bitboard::RANK_2, Bitboard::RANK_7),
+            Color::Black => (Bitboard::RANK_7, Bitboard::RANK_2),
+        };
+
+        for from in pawns {
+            let promotes = promotion_rank.has(from);
+
+            let to = from.shift(offset);
+            if empty.has(to) {
+                if targets.has(to) {
+                    if promotes {
+                        push_promotions(moves, from, to, false);
+                    } else {
+                        moves.push(Move::new(from, to, MoveType::Normal));
+                    }
+                }
+
+                if start_rank.has(from) {
+                    let double_push = to.shift(offset);
+                    if empty.has(double_push) && targets.has(double_push) {
+                        moves.push(Move::new(from, double_push, MoveType::DoublePush));
+                    }
+                }
+            }
+
+            for to in pawn_attacks(from, us) & enemies & targets {
+                if promotes {
+                    push_promotions(moves, from, to, true);
+                } else {
+                    moves.push(Move::new(from, to, MoveType::Capture));
+                }
+            }
+
+            if self.en_passant_square != Square::None
+                && pawn_attacks(from, us).has(self.en_passant_square)
+            {
+                moves.push(Move::new(from, self.en_passant_square, MoveType::EnPassant));
+            }
+        }
+    }
+
+    /// Generates the moves of every `piece` of the side to move landing on `targets`.
+    /// `attacks` maps a square and the board occupancy to the attacked squares.
+    fn generate_piece_moves(
+        &self,
+        moves: &mut MoveList,
+        piece: Piece,
+        targets: Bitboard,
+        attacks: impl Fn(Square, Bitboard) -> Bitboard,
+    ) {
+        let us = self.current_color;
+        let occupancy = self.occupancy();
+        let enemies = self.colors(us.other());
+
+        for from in self.pieces(piece.with_color(us)) {
+            for to in attacks(from, occupancy) & targets {
+                let ty = if enemies.has(to) {
+                    MoveType::Capture
+                } else {
+                    MoveType::Normal
+                };
+                moves.push(Move::new(from, to, ty));
+            }
+        }
+    }
+
+    fn generate_castling_moves(&self, moves: &mut MoveList) {
+        let us = self.current_color;
+        let enemies = self.colors(us.other());
+        let king = self.castling_rights.king_square(us);
+
+        for right in self.castling_rights.iter() {
+            if right.color() != us {
+                continue;
+            }
+
+            let rook = self.castling_rights.rook_square(right);
+            let mv = Move::new(king, rook, MoveType::Castling);
+            let (king_to, rook_to) = mv.castling_destinations();
+
+            // Apart from king and rook themselves, everything both of them cross has to be
+            // empty. This also works for Chess960, where the paths may overlap the origins.
+            let occupancy = self.occupancy() ^ king.bitboard() ^ rook.bitboard();
+            let king_path = between(king, king_to) | king.bitboard() | king_to.bitboard();
+            let rook_path = between(rook, rook_to) | rook_to.bitboard();
+            if ((king_path | rook_path) & occupancy).is_not_empty() {
+                continue;
+            }
+
+            // The king must not start on, pass through or land on an attacked square. The rook
+            // is lifted as well, since in Chess960 it may be shielding the king's destination.
+            if king_path
+                .into_iter()
+                .any(|square| (self.attackers_to(square, occupancy) & enemies).is_not_empty())
+            {
+                continue;
+            }
+
+            moves.push(mv);
+        }
+    }
+}
+
+fn push_promotions(moves: &mut MoveList, from: Square, to: Square, capture: bool) {
+    let types = if capture {
+        [
+            MoveType::PromotionCaptureQueen,
+            MoveType::PromotionCaptureRook,
+            MoveType::PromotionCaptureBishop,
+            MoveType::PromotionCaptureKnight,
+        ]
+    } else {
+        [
+            MoveType::PromotionQueen,
+            MoveType::PromotionRook,
+            MoveType::PromotionBishop,
+            MoveType::PromotionKnight,
+        ]
+    };
+
+    for ty in types {
+        moves.push(Move::new(from, to, ty));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_pseudo_legal(fen: &str) -> usize {
+        Board::from_fen(fen).unwrap().pseudo_legal_moves().len()
+    }
+
+    #[test]
+    fn pseudo_legal_starting_position() {
+        assert_eq!(Board::starting_position().pseudo_legal_moves().len(), 20);
+    }
+
+    #[test]
+    fn pseudo_legal_promotions() {
+        assert_eq!(count_pseudo_legal("8/P7/8/8/8/8/8/k6K w - - 0 1"), 7);
+        assert_eq!(count_pseudo_legal("1n6/P7/8/8/8/8/8/k6K w - - 0 1"), 11);
+
+        let moves = Board::from_fen("1n6/P7/8/8/8/8/8/k6K w - - 0 1")
+            .unwrap()
+            .pseudo_legal_moves();
+        assert!(moves.contains(&Move::new(
+            Square::A7,
+            Square::B8,
+            MoveType::PromotionCaptureKnight
+        )));
+        assert!(moves.contains(&Move::new(Square::A7, Square::A8, MoveType::PromotionQueen)));
+    }
+
+    #[test]
+    fn pseudo_legal_en_passant() {
+        let moves = Board::from_fen("k7/8/8/3pP3/8/8/8/7K w - d6 0 1")
+            .unwrap()
+            .pseudo_legal_moves();
+
+        assert_eq!(moves.len(), 5);
+        assert!(moves.contains(&Move::new(Square::E5, Square::D6, MoveType::EnPassant)));
+    }
+
+    #[test]
+    fn pseudo_legal_double_push() {
+        let moves = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1")
+            .unwrap()
+            .pseudo_legal_moves();
+
+        assert!(moves.contains(&Move::new(Square::E2, Square::E4, MoveType::DoublePush)));
+        assert!(!moves.contains(&Move::new(Square::E2, Square::E4, MoveType::Normal)));
+    }
+
+    fn count_legal(fen: &str) -> usize {
+        Board::from_fen(fen).unwrap().legal_moves().len()
+    }
+
+    #[test]
+    fn legal_standard_positions() {
+        assert_eq!(Board::starting_position().legal_moves().len(), 20);
+        assert_eq!(count_legal("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"), 14);
+        assert_eq!(
+            count_legal("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"),
+            6
+        );
+        assert_eq!(
+            count_legal("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10"),
+            46
+        );
+    }
+
+    #[test]
+    fn legal_castling_positions() {
+        assert_eq!(
+            count_legal("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"),
+            48
+        );
+        assert_eq!(
+            count_legal("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8"),
+            44
+        );
+    }
+
+    #[test]
+    fn legal_castling_through_attacked_square() {
+        // The rook on f8 covers f1, the bishop on a2 does not stop queenside castling
+        let moves = Board::from_fen("r3kr2/8/8/8/8/8/b7/R3K2R w KQ - 0 1")
+            .unwrap()
+            .legal_moves();
+
+        assert!(!moves.contains(&Move::new(Square::E1, Square::H1, MoveType::Castling)));
+        assert!(moves.contains(&Move::new(Square::E1, Square::A1, MoveType::Castling)));
+    }
+
+    #[test]
+    fn legal_double_check_only_king_moves() {
+        let moves = Board::from_fen("4k3/8/8/8/8/5n2/6B1/r3K3 w - - 0 1")
+            .unwrap()
+            .legal_moves();
+
+        assert_eq!(moves.len(), 2);
+        assert!(moves.iter().all(|mv| mv.from() == Square::E1));
+    }
+
+    #[test]
+    fn legal_en_passant_discovered_check() {
+        let moves = Board::from_fen("8/8/8/KPp4r/8/8/8/7k w - c6 0 1")
+            .unwrap()
+            .legal_moves();
+
+        assert_eq!(moves.len(), 4);
+        assert!(!moves.contains(&Move::new(Square::B5, Square::C6, MoveType::EnPassant)));
+    }
+
+    #[test]
+    fn legal_en_passant_evades_pawn_check() {
+        let moves = Board::from_fen("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1")
+            .unwrap()
+            .legal_moves();
+
+        assert!(moves.contains(&Move::new(Square::E4, Square::D3, MoveType::EnPassant)));
+    }
+}
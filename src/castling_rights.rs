@@ -1,18 +1,37 @@
-use crate::castling_rights::CastlingRight::{
-    BlackKingside, BlackQueenside, WhiteKingside, WhiteQueenside,
-};
-use std::fmt::{Display, Formatter};
+use crate::bitboard::Bitboard;
+use crate::color::Color;
+use crate::square::Square;
+use std::fmt::{Display, Formatter, Write};
 
+/// A single castling right. The discriminants are distinct bit flags, so any combination of
+/// rights fits into the `raw` byte of [CastlingRights].
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(u8)]
 pub enum CastlingRight {
-    WhiteKingside,
-    BlackKingside,
-    WhiteQueenside,
-    BlackQueenside,
+    WhiteKingside = 0b0001,
+    WhiteQueenside = 0b0010,
+    BlackKingside = 0b0100,
+    BlackQueenside = 0b1000,
 }
 
 impl CastlingRight {
+    /// All rights in FEN order
+    pub const ALL: [CastlingRight; 4] = [
+        CastlingRight::WhiteKingside,
+        CastlingRight::WhiteQueenside,
+        CastlingRight::BlackKingside,
+        CastlingRight::BlackQueenside,
+    ];
+
+    pub fn new(color: Color, kingside: bool) -> Self {
+        match (color, kingside) {
+            (Color::White, true) => CastlingRight::WhiteKingside,
+            (Color::White, false) => CastlingRight::WhiteQueenside,
+            (Color::Black, true) => CastlingRight::BlackKingside,
+            (Color::Black, false) => CastlingRight::BlackQueenside,
+        }
+    }
+
     pub fn char(&self) -> char {
         match self {
             CastlingRight::WhiteKingside => 'K',
@@ -21,52 +40,271 @@ impl CastlingRight {
             CastlingRight::BlackQueenside => 'q',
         }
     }
+
+    pub fn color(self) -> Color {
+        match self {
+            CastlingRight::WhiteKingside | CastlingRight::WhiteQueenside => Color::White,
+            CastlingRight::BlackKingside | CastlingRight::BlackQueenside => Color::Black,
+        }
+    }
+
+    pub fn is_kingside(self) -> bool {
+        matches!(
+            self,
+            CastlingRight::WhiteKingside | CastlingRight::BlackKingside
+        )
+    }
+
+    /// Position in [CastlingRight::ALL]
+    fn index(self) -> usize {
+        (self as u8).trailing_zeros() as usize
+    }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+/// The held castling rights together with the home squares of the kings and castling rooks.
+/// In standard chess these are always e1/e8 and the corners, in
+/// [Chess960](https://www.chessprogramming.org/Chess960) they depend on the start position.
+///
+/// Two sets of rights are equal if they hold the same rights with the same home squares.
+#[derive(Debug, Copy, Clone)]
 pub struct CastlingRights {
     raw: u8,
+    kings: [Square; 2],
+    /// Indexed like [CastlingRight::ALL]
+    rooks: [Square; 4],
+    /// The rights kept by a move from or to each square, see [CastlingRights::update]
+    update_masks: [u8; 64],
 }
 
 impl CastlingRights {
     pub fn none() -> Self {
-        Self { raw: 0 }
+        Self {
+            raw: 0,
+            kings: [Square::E1, Square::E8],
+            rooks: [Square::H1, Square::A1, Square::H8, Square::A8],
+            update_masks: [0b1111; 64],
+        }
     }
 
+    /// All rights of standard chess
     pub fn all() -> Self {
-        Self {
-            raw: CastlingRight::WhiteKingside as u8
-                | CastlingRight::BlackKingside as u8
-                | CastlingRight::WhiteQueenside as u8
-                | CastlingRight::BlackQueenside as u8,
+        let mut rights = Self::none();
+        for right in CastlingRight::ALL {
+            let rook = rights.rook_square(right);
+            rights.add(right, rights.king_square(right.color()), rook);
         }
+        rights
+    }
+
+    /// The rights as a 4 bit set, e.g. to index tables
+    pub fn raw(&self) -> u8 {
+        self.raw
     }
 
     pub fn can_castle(&self, castling_right: CastlingRight) -> bool {
         self.raw & castling_right as u8 != 0
     }
 
-    pub fn add(&mut self, castling_right: CastlingRight) {
+    /// Adds a right for the king on `king` castling with the rook on `rook`.
+    pub fn add(&mut self, castling_right: CastlingRight, king: Square, rook: Square) {
         self.raw |= castling_right as u8;
+        self.kings[castling_right.color()] = king;
+        self.rooks[castling_right.index()] = rook;
+        self.update_masks[king] &= !(castling_right as u8);
+        self.update_masks[rook] &= !(castling_right as u8);
+    }
+
+    /// Restores rights previously returned by [CastlingRights::raw], keeping the home squares.
+    pub(crate) fn restore(&mut self, raw: u8) {
+        self.raw = raw;
     }
 
     pub fn remove(&mut self, castling_right: CastlingRight) {
         self.raw &= !(castling_right as u8);
     }
+
+    /// Whether all held rights use the kings and rooks of standard chess. Otherwise the position
+    /// can only be a Chess960 one.
+    pub fn is_standard(&self) -> bool {
+        let standard = Self::all();
+        self.iter().all(|right| {
+            self.king_square(right.color()) == standard.king_square(right.color())
+                && self.rook_square(right) == standard.rook_square(right)
+        })
+    }
+
+    /// Home square of the king of `color`, only meaningful while it holds a right
+    pub fn king_square(&self, color: Color) -> Square {
+        self.kings[color]
+    }
+
+    /// Home square of the rook used by `castling_right`
+    pub fn rook_square(&self, castling_right: CastlingRight) -> Square {
+        self.rooks[castling_right.index()]
+    }
+
+    /// Removes the rights lost by a move from `from` to `to`. Moving the king or a rook from its
+    /// home square, or capturing a rook there, removes the corresponding rights, see
+    /// [here](https://www.chessprogramming.org/Castling_Rights#Update_by_Square).
+    pub fn update(&mut self, from: Square, to: Square) {
+        self.raw &= self.update_masks[from] & self.update_masks[to];
+    }
+
+    /// Writes the rights as in [X-FEN](https://en.wikipedia.org/wiki/X-FEN): `KQkq` if the
+    /// castling rook is the outermost of the `rooks` of its color on that side, otherwise the
+    /// rook's file as in Shredder-FEN. With `shredder` every right is written with its file.
+    pub(crate) fn write_fen(
+        &self,
+        f: &mut impl Write,
+        rooks: [Bitboard; 2],
+        shredder: bool,
+    ) -> std::fmt::Result {
+        if self.raw == 0 {
+            return f.write_char('-');
+        }
+
+        for right in self.iter() {
+            let rook = self.rook_square(right);
+            if shredder || !is_outermost_rook(right, rook, rooks[right.color()]) {
+                f.write_char(shredder_char(right, rook))?;
+            } else {
+                f.write_char(right.char())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Iterates over the held rights in FEN order.
+    pub fn iter(&self) -> impl Iterator<Item = CastlingRight> + use<> {
+        let rights = *self;
+        CastlingRight::ALL
+            .into_iter()
+            .filter(move |&right| rights.can_castle(right))
+    }
+}
+
+impl PartialEq for CastlingRights {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+            && self.iter().all(|right| {
+                self.king_square(right.color()) == other.king_square(right.color())
+                    && self.rook_square(right) == other.rook_square(right)
+            })
+    }
+}
+
+impl Eq for CastlingRights {}
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+        Self::none()
+    }
 }
 
+/// Formats the rights as in X-FEN, e.g. `KQkq`, see [CastlingRights::write_fen]. Only the
+/// castling rooks are known here, so this matches [Board::fen](crate::board::Board::fen) unless
+/// another rook stands further out on the castling side.
 impl Display for CastlingRights {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if *self == CastlingRights::none() {
-            return write!(f, "-");
+        let mut rooks = [Bitboard::default(); 2];
+        for right in self.iter() {
+            rooks[right.color()].add(self.rook_square(right));
         }
+        self.write_fen(f, rooks, false)
+    }
+}
 
-        for right in [WhiteKingside, BlackKingside, WhiteQueenside, BlackQueenside].iter() {
-            if self.can_castle(*right) {
-                write!(f, "{}", right.char())?;
+/// Whether no rook in `rooks` stands further out than `rook` on the side of `right`
+fn is_outermost_rook(right: CastlingRight, rook: Square, rooks: Bitboard) -> bool {
+    rooks.into_iter().all(|other| {
+        other.rank() != rook.rank()
+            || if right.is_kingside() {
+                other.file() <= rook.file()
+            } else {
+                other.file() >= rook.file()
             }
-        }
+    })
+}
 
-        Ok(())
+/// The file of the castling rook as used in Shredder-FEN, uppercase for white
+fn shredder_char(right: CastlingRight, rook: Square) -> char {
+    let file = (b'a' + rook.file()) as char;
+    match right.color() {
+        Color::White => file.to_ascii_uppercase(),
+        Color::Black => file,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_rights_are_distinct() {
+        let rights = CastlingRights::all();
+
+        assert_eq!(rights.iter().count(), 4);
+        assert_eq!(rights.to_string(), "KQkq");
+        assert_eq!(CastlingRights::none().to_string(), "-");
+        assert!(rights.is_standard());
+    }
+
+    #[test]
+    fn update_by_square() {
+        let mut rights = CastlingRights::all();
+
+        rights.update(Square::H1, Square::H5);
+        assert_eq!(rights.to_string(), "Qkq");
+
+        rights.update(Square::B2, Square::A8);
+        assert_eq!(rights.to_string(), "Qk");
+
+        rights.update(Square::E8, Square::E7);
+        assert_eq!(rights.to_string(), "Q");
+
+        rights.update(Square::E1, Square::D1);
+        assert_eq!(rights, CastlingRights::none());
+    }
+
+    #[test]
+    fn update_chess960() {
+        let mut rights = CastlingRights::none();
+        rights.add(CastlingRight::WhiteKingside, Square::B1, Square::C1);
+        rights.add(CastlingRight::WhiteQueenside, Square::B1, Square::A1);
+        assert_eq!(rights.to_string(), "KQ");
+        assert!(!rights.is_standard());
+
+        rights.update(Square::H1, Square::H5);
+        assert_eq!(rights.to_string(), "KQ");
+
+        rights.update(Square::C1, Square::C5);
+        assert_eq!(rights.to_string(), "Q");
+
+        rights.update(Square::B1, Square::B2);
+        assert_eq!(rights.to_string(), "-");
+    }
+
+    #[test]
+    fn fen_formats() {
+        let mut rights = CastlingRights::none();
+        rights.add(CastlingRight::WhiteKingside, Square::B1, Square::C1);
+        rights.add(CastlingRight::BlackQueenside, Square::G8, Square::F8);
+
+        let write = |rooks: [Square; 2], shredder| {
+            let rooks = rooks.map(|square| {
+                let mut bitboard = Bitboard::default();
+                bitboard.add(square);
+                bitboard
+            });
+            let mut fen = String::new();
+            rights.write_fen(&mut fen, rooks, shredder).unwrap();
+            fen
+        };
+
+        // An extra rook further out forces the file
+        assert_eq!(write([Square::A1, Square::H8], false), "Kq");
+        assert_eq!(write([Square::H1, Square::A8], false), "Cf");
+        assert_eq!(write([Square::A1, Square::H8], true), "Cf");
     }
 }
@@ -1,5 +1,7 @@
+use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::ops::{Index, IndexMut};
+use std::str::FromStr;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
 pub enum Color {
@@ -9,10 +11,12 @@ pub enum Color {
 }
 
 impl Color {
-    pub fn new(value: u8) -> Self {
+    /// Unchecked conversion for trusted values, use [Color::try_from] for untrusted input
+    pub(crate) fn new(value: u8) -> Self {
         debug_assert!(value < 2);
         unsafe { std::mem::transmute(value) }
     }
+
     pub fn other(self) -> Color {
         match self {
             Color::White => Color::Black,
@@ -20,6 +24,14 @@ impl Color {
         }
     }
 
+    /// Square offset of a single pawn push for this color.
+    pub fn pawn_push_offset(self) -> i8 {
+        match self {
+            Color::White => 8,
+            Color::Black => -8,
+        }
+    }
+
     pub fn char(self) -> char {
         match self {
             Color::White => 'w',
@@ -34,6 +46,54 @@ impl Display for Color {
     }
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ParseColorError;
+
+impl Display for ParseColorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid color, expected 'w' or 'b'")
+    }
+}
+
+impl Error for ParseColorError {}
+
+/// Parses the FEN side to move, `w` or `b`
+impl TryFrom<char> for Color {
+    type Error = ParseColorError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'w' => Ok(Color::White),
+            'b' => Ok(Color::Black),
+            _ => Err(ParseColorError),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Color::try_from(c),
+            _ => Err(ParseColorError),
+        }
+    }
+}
+
+impl TryFrom<u8> for Color {
+    type Error = ParseColorError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Color::White),
+            1 => Ok(Color::Black),
+            _ => Err(ParseColorError),
+        }
+    }
+}
+
 impl<T> Index<Color> for [T] {
     type Output = T;
 
@@ -0,0 +1,411 @@
+use crate::board::Board;
+use crate::color::Color::{self, Black, White};
+use crate::pawns::{PawnStructure, PawnTable};
+use crate::piece::Piece;
+use crate::square::Square;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+/// Material values of pawn, knight, bishop, rook, queen and king in centipawns, as used for
+/// exchanges and move ordering
+pub const PIECE_VALUES: [i32; 6] = [100, 320, 330, 500, 900, 0];
+
+pub fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::None => 0,
+        _ => PIECE_VALUES[piece as usize / 2],
+    }
+}
+
+/// A score for the middlegame and one for the endgame, blended by [PhaseScore::taper].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PhaseScore {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl PhaseScore {
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Self { mg, eg }
+    }
+
+    /// Interpolates between the endgame score at phase 0 and the middlegame score at
+    /// [MAX_PHASE].
+    pub fn taper(self, phase: i32) -> i32 {
+        let phase = phase.min(MAX_PHASE);
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for PhaseScore {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.mg + rhs.mg, self.eg + rhs.eg)
+    }
+}
+
+impl Sub for PhaseScore {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.mg - rhs.mg, self.eg - rhs.eg)
+    }
+}
+
+impl Neg for PhaseScore {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.mg, -self.eg)
+    }
+}
+
+impl AddAssign for PhaseScore {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for PhaseScore {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+/// Game phase of the starting position. More material (e.g. after promotions) is clamped.
+pub const MAX_PHASE: i32 = 24;
+
+/// Contribution of pawn, knight, bishop, rook, queen and king to the game phase
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+
+const MATERIAL: [PhaseScore; 6] = [
+    PhaseScore::new(100, 120),
+    PhaseScore::new(320, 300),
+    PhaseScore::new(330, 320),
+    PhaseScore::new(500, 540),
+    PhaseScore::new(900, 950),
+    PhaseScore::new(0, 0),
+];
+
+// Piece-square tables from white's point of view, written as seen from white with a8 first.
+// Most of them are taken from the Simplified Evaluation Function, see
+// [here](https://www.chessprogramming.org/Simplified_Evaluation_Function).
+
+#[rustfmt::skip]
+const PAWN_MG: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    10,  10,  20,  30,  30,  20,  10,  10,
+     5,   5,  10,  25,  25,  10,   5,   5,
+     0,   0,   0,  20,  20,   0,   0,   0,
+     5,  -5, -10,   0,   0, -10,  -5,   5,
+     5,  10,  10, -20, -20,  10,  10,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+/// Passers decide endgames, so advanced pawns are worth more everywhere
+#[rustfmt::skip]
+const PAWN_EG: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    80,  80,  80,  80,  80,  80,  80,  80,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    30,  30,  30,  30,  30,  30,  30,  30,
+    15,  15,  15,  15,  15,  15,  15,  15,
+     5,   5,   5,   5,   5,   5,   5,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+   -50, -40, -30, -30, -30, -30, -40, -50,
+   -40, -20,   0,   0,   0,   0, -20, -40,
+   -30,   0,  10,  15,  15,  10,   0, -30,
+   -30,   5,  15,  20,  20,  15,   5, -30,
+   -30,   0,  15,  20,  20,  15,   0, -30,
+   -30,   5,  10,  15,  15,  10,   5, -30,
+   -40, -20,   0,   5,   5,   0, -20, -40,
+   -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+   -20, -10, -10, -10, -10, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,  10,  10,   5,   0, -10,
+   -10,   5,   5,  10,  10,   5,   5, -10,
+   -10,   0,  10,  10,  10,  10,   0, -10,
+   -10,  10,  10,  10,  10,  10,  10, -10,
+   -10,   5,   0,   0,   0,   0,   5, -10,
+   -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+     5,  10,  10,  10,  10,  10,  10,   5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+     0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,   5,   5,   5,   0, -10,
+    -5,   0,   5,   5,   5,   5,   0,  -5,
+     0,   0,   5,   5,   5,   5,   0,  -5,
+   -10,   5,   5,   5,   5,   5,   0, -10,
+   -10,   0,   5,   0,   0,   0,   0, -10,
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+/// Hide behind the pawns while there are pieces around
+#[rustfmt::skip]
+const KING_MG: [i32; 64] = [
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -20, -30, -30, -40, -40, -30, -30, -20,
+   -10, -20, -20, -20, -20, -20, -20, -10,
+    20,  20,   0,   0,   0,   0,  20,  20,
+    20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+/// Walk to the center once the pieces are traded
+#[rustfmt::skip]
+const KING_EG: [i32; 64] = [
+   -50, -40, -30, -20, -20, -30, -40, -50,
+   -30, -20, -10,   0,   0, -10, -20, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -30,   0,   0,   0,   0, -30, -30,
+   -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+const TABLES_MG: [[i32; 64]; 6] = [PAWN_MG, KNIGHT, BISHOP, ROOK, QUEEN, KING_MG];
+const TABLES_EG: [[i32; 64]; 6] = [PAWN_EG, KNIGHT, BISHOP, ROOK, QUEEN, KING_EG];
+
+/// Piece-square bonus of every piece on every square from white's point of view, i.e.
+/// negative for black pieces. Indexed by [Piece] and [Square].
+static PSQT: [[PhaseScore; 64]; 12] = {
+    let mut psqt = [[PhaseScore::new(0, 0); 64]; 12];
+    let mut piece = 0;
+    while piece < 12 {
+        let mut square = 0;
+        while square < 64 {
+            // Black pieces look the tables up mirrored. The tables start at a8, so for white
+            // the square is flipped vertically.
+            let (index, sign) = if piece % 2 == 0 {
+                (square ^ 56, 1)
+            } else {
+                (square, -1)
+            };
+            psqt[piece][square] = PhaseScore::new(
+                sign * TABLES_MG[piece / 2][index],
+                sign * TABLES_EG[piece / 2][index],
+            );
+            square += 1;
+        }
+        piece += 1;
+    }
+    psqt
+};
+
+/// Material and piece-square bonus of `piece` on `square` from white's point of view, as
+/// maintained incrementally by the [Board].
+pub fn piece_square_score(piece: Piece, square: Square) -> PhaseScore {
+    let material = MATERIAL[piece as usize / 2];
+    match piece.get_color() {
+        White => material + PSQT[piece][square],
+        Black => PSQT[piece][square] - material,
+    }
+}
+
+pub fn phase_weight(piece: Piece) -> i32 {
+    PHASE_WEIGHTS[piece as usize / 2]
+}
+
+impl Board {
+    /// Static evaluation from the point of view of the side to move
+    pub fn evaluate(&self) -> i32 {
+        self.evaluate_with(&PawnStructure::new(self))
+    }
+
+    /// Same as [Board::evaluate], but looks the pawn structure up in `pawns`.
+    pub fn evaluate_cached(&self, pawns: &mut PawnTable) -> i32 {
+        self.evaluate_with(&pawns.probe(self))
+    }
+
+    fn evaluate_with(&self, pawns: &PawnStructure) -> i32 {
+        let score = self.piece_square_score() + pawns.score(White) - pawns.score(Black)
+            + pawns.passed_score(self, White)
+            - pawns.passed_score(self, Black);
+        let score = score.taper(self.phase());
+
+        match self.side_to_move() {
+            White => score,
+            Black => -score,
+        }
+    }
+
+    /// Recomputes every evaluation term from scratch, see [EvalTrace].
+    pub fn trace(&self) -> EvalTrace {
+        let mut material = [PhaseScore::default(); 2];
+        let mut psqt = [PhaseScore::default(); 2];
+
+        for square in self.occupancy() {
+            let piece = self.piece_on(square);
+            let color = piece.get_color();
+            let bonus = PSQT[piece][square];
+
+            material[color] += MATERIAL[piece as usize / 2];
+            psqt[color] += if color == White { bonus } else { -bonus };
+        }
+
+        let pawns = PawnStructure::new(self);
+        let structure = [pawns.score(White), pawns.score(Black)];
+        let passed = [
+            pawns.passed_score(self, White),
+            pawns.passed_score(self, Black),
+        ];
+
+        EvalTrace {
+            terms: vec![
+                ("Material", material),
+                ("PSQT", psqt),
+                ("Pawns", structure),
+                ("Passed", passed),
+            ],
+            phase: self.phase(),
+        }
+    }
+}
+
+/// Per-term breakdown of the evaluation, printed by the `eval` UCI command.
+#[derive(Clone, Debug)]
+pub struct EvalTrace {
+    /// Name and score of every term, per color from that color's point of view
+    pub terms: Vec<(&'static str, [PhaseScore; 2])>,
+    pub phase: i32,
+}
+
+impl EvalTrace {
+    /// Sum of all terms from white's point of view
+    pub fn total(&self) -> PhaseScore {
+        self.terms
+            .iter()
+            .fold(PhaseScore::default(), |total, (_, [white, black])| {
+                total + *white - *black
+            })
+    }
+}
+
+impl Display for EvalTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "      Term |    White    |    Black    |    Total")?;
+        writeln!(f, "           |   MG    EG  |   MG    EG  |   MG    EG")?;
+        writeln!(f, "-----------+-------------+-------------+-------------")?;
+
+        let row = |f: &mut Formatter<'_>, name: &str, white: PhaseScore, black: PhaseScore| {
+            let total = white - black;
+            writeln!(
+                f,
+                "{name:>10} | {:>5} {:>5} | {:>5} {:>5} | {:>5} {:>5}",
+                white.mg, white.eg, black.mg, black.eg, total.mg, total.eg
+            )
+        };
+
+        let mut sums = [PhaseScore::default(); 2];
+        for &(name, [white, black]) in &self.terms {
+            row(f, name, white, black)?;
+            sums[Color::White] += white;
+            sums[Color::Black] += black;
+        }
+        writeln!(f, "-----------+-------------+-------------+-------------")?;
+        row(f, "Total", sums[Color::White], sums[Color::Black])?;
+
+        writeln!(f)?;
+        writeln!(f, "Phase: {}/{MAX_PHASE}", self.phase.min(MAX_PHASE))?;
+        writeln!(
+            f,
+            "Final evaluation: {} cp (white side)",
+            self.total().taper(self.phase)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::Move;
+
+    const POSITIONS: [&str; 4] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ];
+
+    #[test]
+    fn symmetric() {
+        assert_eq!(Board::starting_position().evaluate(), 0);
+
+        // Mirrored position with colors swapped
+        let white = Board::from_fen("4k3/8/8/8/3N4/8/PPP5/1K6 w - - 0 1").unwrap();
+        let black = Board::from_fen("1k6/ppp5/8/3n4/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(white.evaluate(), black.evaluate());
+    }
+
+    #[test]
+    fn incremental_matches_trace() {
+        for fen in POSITIONS {
+            let mut board = Board::from_fen(fen).unwrap();
+            for &mv in &board.legal_moves() {
+                let undo = board.make_move(mv);
+                assert_trace_matches(&board, mv);
+                board.unmake_move(mv, undo);
+            }
+            let terms = &board.trace().terms;
+            let psqt = terms[0].1[0] - terms[0].1[1] + terms[1].1[0] - terms[1].1[1];
+            assert_eq!(board.piece_square_score(), psqt);
+        }
+    }
+
+    fn assert_trace_matches(board: &Board, mv: Move) {
+        let trace = board.trace();
+        let white_eval = trace.total().taper(trace.phase);
+        let expected = match board.side_to_move() {
+            White => white_eval,
+            Black => -white_eval,
+        };
+        assert_eq!(board.evaluate(), expected, "{} after {mv}", board.fen());
+    }
+
+    #[test]
+    fn cached_matches_uncached() {
+        let mut pawns = PawnTable::new(64);
+        for fen in POSITIONS {
+            let mut board = Board::from_fen(fen).unwrap();
+            for &mv in &board.legal_moves() {
+                let undo = board.make_move(mv);
+                assert_eq!(board.evaluate_cached(&mut pawns), board.evaluate(), "{mv}");
+                board.unmake_move(mv, undo);
+            }
+        }
+    }
+
+    #[test]
+    fn phase() {
+        assert_eq!(Board::starting_position().phase(), MAX_PHASE);
+        assert_eq!(Board::from_fen(POSITIONS[2]).unwrap().phase(), 4);
+    }
+}
@@ -0,0 +1,278 @@
+use crate::bitboard::Bitboard;
+use crate::board::{Board, Undo};
+use crate::color::Color::{self, Black, White};
+use crate::moves::Move;
+use crate::pgn::GameResult;
+use crate::piece::Piece;
+
+/// Ways a game can end. Following the FIDE Laws of Chess, [Outcome::FiftyMoveRule] and
+/// [Outcome::ThreefoldRepetition] only end the game when a player claims them, all others end
+/// it immediately.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Checkmate {
+        winner: Color,
+    },
+    Stalemate,
+    /// 50 moves by each side without a capture or pawn move, claimable
+    FiftyMoveRule,
+    /// 75 moves by each side without a capture or pawn move
+    SeventyFiveMoveRule,
+    /// Third occurrence of the same position, claimable
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    /// Neither side can checkmate by any series of legal moves
+    InsufficientMaterial,
+}
+
+impl Outcome {
+    pub fn winner(self) -> Option<Color> {
+        match self {
+            Outcome::Checkmate { winner } => Some(winner),
+            _ => None,
+        }
+    }
+
+    /// Whether the game ends without one of the players claiming the draw
+    pub fn is_automatic(self) -> bool {
+        !matches!(self, Outcome::FiftyMoveRule | Outcome::ThreefoldRepetition)
+    }
+}
+
+impl From<Outcome> for GameResult {
+    fn from(outcome: Outcome) -> Self {
+        match outcome.winner() {
+            Some(White) => GameResult::WhiteWins,
+            Some(Black) => GameResult::BlackWins,
+            None => GameResult::Draw,
+        }
+    }
+}
+
+/// A [Board] together with the moves played to reach it, as needed to detect repetitions.
+#[derive(Clone, Debug)]
+pub struct Game {
+    board: Board,
+    /// Keys of the positions played before `board`
+    history: Vec<u64>,
+    moves: Vec<(Move, Undo)>,
+}
+
+impl Game {
+    /// Starts a game from `board`. Positions before it are unknown and never count as
+    /// repetitions.
+    pub fn new(board: Board) -> Self {
+        Self {
+            board,
+            history: Vec::new(),
+            moves: Vec::new(),
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Keys of the positions played before the current one, oldest first
+    pub fn history(&self) -> &[u64] {
+        &self.history
+    }
+
+    /// The moves played since [Game::new], oldest first
+    pub fn moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.moves.iter().map(|&(mv, _)| mv)
+    }
+
+    /// Plays `mv`, which has to be legal in the current position.
+    pub fn make_move(&mut self, mv: Move) {
+        self.history.push(self.board.hash());
+        let undo = self.board.make_move(mv);
+        self.moves.push((mv, undo));
+    }
+
+    /// Takes back the last move, returns [None] at the start of the game.
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let (mv, undo) = self.moves.pop()?;
+        self.board.unmake_move(mv, undo);
+        self.history.pop();
+        Some(mv)
+    }
+
+    /// How often the current position occurred in the game, including now. Only positions
+    /// since the last capture or pawn move can be equal.
+    pub fn repetitions(&self) -> usize {
+        let hash = self.board.hash();
+        let earlier = self
+            .history
+            .iter()
+            .rev()
+            .take(self.board.half_moves())
+            .skip(1)
+            .step_by(2)
+            .filter(|&&key| key == hash)
+            .count();
+
+        earlier + 1
+    }
+
+    /// The outcome if the game is over without any claim, see [Outcome::is_automatic].
+    pub fn outcome(&self) -> Option<Outcome> {
+        // Checkmate takes precedence over the 75-move rule
+        if self.board.legal_moves().is_empty() {
+            return Some(if self.board.in_check() {
+                Outcome::Checkmate {
+                    winner: self.board.opponent(),
+                }
+            } else {
+                Outcome::Stalemate
+            });
+        }
+
+        if self.board.is_insufficient_material() {
+            Some(Outcome::InsufficientMaterial)
+        } else if self.board.half_moves() >= 150 {
+            Some(Outcome::SeventyFiveMoveRule)
+        } else if self.repetitions() >= 5 {
+            Some(Outcome::FivefoldRepetition)
+        } else {
+            None
+        }
+    }
+
+    /// A draw the side to move may claim, if the game is not already over.
+    pub fn claimable_draw(&self) -> Option<Outcome> {
+        if self.outcome().is_some() {
+            None
+        } else if self.board.half_moves() >= 100 {
+            Some(Outcome::FiftyMoveRule)
+        } else if self.repetitions() >= 3 {
+            Some(Outcome::ThreefoldRepetition)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Board::starting_position())
+    }
+}
+
+impl Board {
+    /// King against king with at most one minor piece, or only bishops that are all on
+    /// squares of the same color. Positions like KN vs KN are not included, since a mate is
+    /// still possible with help from the opponent.
+    pub fn is_insufficient_material(&self) -> bool {
+        let pieces = |piece: Piece| self.pieces(piece) | self.pieces(piece.with_color(Black));
+        let heavy = pieces(Piece::WhitePawn) | pieces(Piece::WhiteRook) | pieces(Piece::WhiteQueen);
+        if heavy.is_not_empty() {
+            return false;
+        }
+
+        let knights = pieces(Piece::WhiteKnight);
+        let bishops = pieces(Piece::WhiteBishop);
+        if (knights | bishops).pop_count() <= 1 {
+            return true;
+        }
+
+        knights.is_empty()
+            && ((bishops & Bitboard::LIGHT_SQUARES).is_empty()
+                || (bishops & !Bitboard::LIGHT_SQUARES).is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(game: &mut Game, moves: &str) {
+        for text in moves.split_whitespace() {
+            let mv = game.board().parse_uci_move(text).unwrap();
+            game.make_move(mv);
+        }
+    }
+
+    fn outcome(fen: &str) -> Option<Outcome> {
+        Game::new(Board::from_fen(fen).unwrap()).outcome()
+    }
+
+    #[test]
+    fn checkmate_and_stalemate() {
+        let mut game = Game::default();
+        play(&mut game, "f2f3 e7e5 g2g4");
+        assert_eq!(game.outcome(), None);
+        play(&mut game, "d8h4");
+        assert_eq!(game.outcome(), Some(Outcome::Checkmate { winner: Black }));
+        assert_eq!(
+            GameResult::from(game.outcome().unwrap()),
+            GameResult::BlackWins
+        );
+
+        assert_eq!(
+            outcome("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"),
+            Some(Outcome::Stalemate)
+        );
+        assert_eq!(
+            outcome("7k/6Q1/6K1/8/8/8/8/8 b - - 150 100"),
+            Some(Outcome::Checkmate { winner: White })
+        );
+    }
+
+    #[test]
+    fn insufficient_material() {
+        for (fen, insufficient) in [
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/4KN2 w - - 0 1", true),
+            ("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/1BB1K3 w - - 0 1", false),
+            ("4kn2/8/8/8/8/8/8/4KN2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", false),
+        ] {
+            assert_eq!(
+                Board::from_fen(fen).unwrap().is_insufficient_material(),
+                insufficient,
+                "{fen}"
+            );
+        }
+    }
+
+    #[test]
+    fn move_rules() {
+        let game = |half_moves: usize| {
+            let fen = format!("4k3/8/8/8/8/8/8/R3K3 w - - {half_moves} 80");
+            Game::new(Board::from_fen(&fen).unwrap())
+        };
+
+        assert_eq!(game(99).claimable_draw(), None);
+        assert_eq!(game(100).claimable_draw(), Some(Outcome::FiftyMoveRule));
+        assert_eq!(game(100).outcome(), None);
+        assert_eq!(game(150).outcome(), Some(Outcome::SeventyFiveMoveRule));
+    }
+
+    #[test]
+    fn repetitions() {
+        let mut game = Game::default();
+        let shuffle = "g1f3 g8f6 f3g1 f6g8";
+
+        play(&mut game, shuffle);
+        assert_eq!(game.repetitions(), 2);
+        assert_eq!(game.claimable_draw(), None);
+
+        play(&mut game, shuffle);
+        assert_eq!(game.claimable_draw(), Some(Outcome::ThreefoldRepetition));
+        assert_eq!(game.outcome(), None);
+
+        play(&mut game, shuffle);
+        play(&mut game, shuffle);
+        assert_eq!(game.repetitions(), 5);
+        assert_eq!(game.outcome(), Some(Outcome::FivefoldRepetition));
+
+        assert_eq!(
+            game.unmake_move().map(|mv| mv.to_string()),
+            Some("f6g8".to_string())
+        );
+        assert_eq!(game.outcome(), None);
+        assert_eq!(game.moves().count(), 15);
+    }
+}
@@ -0,0 +1,38 @@
+//! Chess move generation, notation and search behind the croissant-chess UCI engine.
+//!
+//! ```
+//! use croissant_chess::Board;
+//!
+//! let mut board = Board::starting_position();
+//! let mv = board.parse_san("e4").unwrap();
+//! board.make_move(mv);
+//! assert_eq!(board.legal_moves().len(), 20);
+//! ```
+
+pub mod bitboard;
+pub mod board;
+pub mod castling_rights;
+pub mod color;
+pub mod evaluation;
+pub mod game;
+pub mod moves;
+pub mod notation;
+pub mod pawns;
+mod perft;
+pub mod pgn;
+pub mod piece;
+pub mod score;
+pub mod search;
+mod see;
+pub mod square;
+pub mod transposition;
+pub mod uci;
+mod zobrist;
+
+pub use bitboard::Bitboard;
+pub use board::{Board, FenError};
+pub use color::Color;
+pub use game::{Game, Outcome};
+pub use moves::{Move, MoveList, MoveType};
+pub use piece::Piece;
+pub use square::Square;
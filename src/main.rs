@@ -1,22 +1,60 @@
-use crate::board::Board;
-use crate::moves::Move;
-use crate::moves::MoveType::Castling;
-use crate::square::Square::{E8, G8};
-
-mod bitboard;
-mod board;
-mod castling_rights;
-mod color;
-mod moves;
-mod piece;
-mod square;
-
-fn main() {
-    let mut board =
-        Board::from_fen("r3k2r/pp1p1ppp/8/1Bp1p3/4P3/5N2/PPPP1PPP/R3K2R b KQkq - 0 1").unwrap();
-    println!("{board}");
-
-    let mv = Move::new(E8, G8, Castling);
-    board.make_move(mv);
-    println!("{board}");
+use croissant_chess::Board;
+use croissant_chess::uci::Uci;
+use std::process::ExitCode;
+use std::time::Instant;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.first().is_some_and(|command| command == "perft") {
+        return perft(&args[1..]);
+    }
+
+    Uci::new().run();
+    ExitCode::SUCCESS
+}
+
+/// `perft <depth> [fen]`: prints a perft divide of the given position, or the starting
+/// position if no FEN is given, in the same format as Stockfish's `go perft`.
+fn perft(args: &[String]) -> ExitCode {
+    let Some(depth) = args.first().and_then(|depth| depth.parse().ok()) else {
+        eprintln!("Usage: croissant-chess perft <depth> [fen]");
+        return ExitCode::FAILURE;
+    };
+
+    let mut board = if args.len() > 1 {
+        match Board::from_fen(&args[1..].join(" ")) {
+            Ok(board) => board,
+            Err(error) => {
+                eprintln!("Invalid FEN: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        Board::starting_position()
+    };
+
+    let start = Instant::now();
+    let divide = board.perft_divide(depth);
+    // Without root moves (depth 0 or game over) the position itself is the whole tree
+    let nodes = if divide.is_empty() {
+        board.perft(depth)
+    } else {
+        divide.iter().map(|&(_, nodes)| nodes).sum()
+    };
+    let elapsed = start.elapsed();
+
+    for (mv, nodes) in divide {
+        println!("{mv}: {nodes}");
+    }
+    println!();
+    println!("Nodes searched: {nodes}");
+
+    println!(
+        "Time: {} ms ({:.0} nps)",
+        elapsed.as_millis(),
+        nodes as f64 / elapsed.as_secs_f64()
+    );
+
+    ExitCode::SUCCESS
 }
@@ -1,7 +1,12 @@
+use crate::color::Color;
 use crate::piece::Piece;
 use crate::square::Square;
-use crate::square::Square::{A1, A8, C1, C8, D1, D8, F1, F8, G1, G8, H1, H8};
+use std::error::Error;
 use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// Upper bound for the number of moves in any reachable position (the known maximum is 218).
+pub const MAX_MOVES: usize = 256;
 
 /// # Memory Layout
 /// \[FLAGS: 4 bit]\[TO: 6 bit]\[FROM: 6 bit]
@@ -28,8 +33,28 @@ pub enum MoveType {
 }
 
 impl Move {
+    /// Packs a move. [Square::None] is stored as a1, so the flags are never corrupted.
     pub fn new(from: Square, to: Square, ty: MoveType) -> Self {
-        Self(from as u16 | ((to as u16) << 6) | ((ty as u16) << 12))
+        Self((from as u16 & 0b111111) | ((to as u16 & 0b111111) << 6) | ((ty as u16) << 12))
+    }
+
+    /// The packed 16 bit representation, e.g. for storing the move in the transposition table
+    pub fn raw(self) -> u16 {
+        self.0
+    }
+
+    /// Inverse of [Move::raw]. The value must come from a valid move, since the flags are
+    /// transmuted into a [MoveType]. Use [Move::try_from] for untrusted values.
+    pub(crate) fn from_raw(raw: u16) -> Self {
+        Self(raw)
+    }
+
+    pub fn is_capture(self) -> bool {
+        self.ty() as u8 & 0b0100 != 0
+    }
+
+    pub fn is_promotion(self) -> bool {
+        self.ty() as u8 & 0b1000 != 0
     }
 
     pub fn from(self) -> Square {
@@ -40,13 +65,22 @@ impl Move {
         Square::new(((self.0 & (0b111111 << 6)) >> 6) as u8)
     }
 
-    pub fn get_rook_from_to_square_castling(&self) -> (Square, Square) {
-        match self.to() {
-            G1 => (H1, F1),
-            C1 => (A1, D1),
-            G8 => (H8, F8),
-            C8 => (A8, D8),
-            _ => (Square::None, Square::None),
+    /// Destinations of king and rook for a castling move. Castling is encoded as the king
+    /// capturing its own rook, which also works for [Chess960](https://www.chessprogramming.org/Chess960)
+    /// positions where the king does not move two squares. King and rook end up on the g and f
+    /// files for kingside castling and on the c and d files for queenside castling.
+    pub fn castling_destinations(self) -> (Square, Square) {
+        let rank = self.from().rank();
+        if self.to().file() > self.from().file() {
+            (
+                Square::from_file_and_rank(6, rank),
+                Square::from_file_and_rank(5, rank),
+            )
+        } else {
+            (
+                Square::from_file_and_rank(2, rank),
+                Square::from_file_and_rank(3, rank),
+            )
         }
     }
 
@@ -57,7 +91,7 @@ impl Move {
     pub fn get_promotion(&self) -> Option<Piece> {
         match self.ty() {
             MoveType::PromotionKnight | MoveType::PromotionCaptureKnight => {
-                Some(Piece::WhiteBishop)
+                Some(Piece::WhiteKnight)
             }
             MoveType::PromotionRook | MoveType::PromotionCaptureRook => Some(Piece::WhiteRook),
             MoveType::PromotionBishop | MoveType::PromotionCaptureBishop => {
@@ -69,18 +103,120 @@ impl Move {
     }
 }
 
+/// Error for packed values that do not encode a move
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct InvalidMoveError;
+
+impl Display for InvalidMoveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid move encoding")
+    }
+}
+
+impl Error for InvalidMoveError {}
+
+/// Checked inverse of [Move::raw]. Rejects unused flag values and moves to the origin square,
+/// legality in a position is not checked.
+impl TryFrom<u16> for Move {
+    type Error = InvalidMoveError;
+
+    fn try_from(raw: u16) -> Result<Self, Self::Error> {
+        let flags = raw >> 12;
+        let mv = Self(raw);
+        if matches!(flags, 0b0011 | 0b0110 | 0b0111) || mv.from() == mv.to() {
+            return Err(InvalidMoveError);
+        }
+
+        Ok(mv)
+    }
+}
+
+/// Formats the move in long algebraic notation as used by UCI, e.g. `e2e4` or `e7e8q`.
+///
+/// Castling is written as the king's move (`e1g1`), or with the alternate flag (`{:#}`) as the
+/// king capturing its rook (`e1h1`) as required by UCI in Chess960 mode.
 impl Display for Move {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if let Some(promotion) = self.get_promotion() {
-            writeln!(
-                f,
-                "{} → {} promoting to {}",
-                self.from(),
-                self.to(),
-                promotion
-            )
+        let to = if self.ty() == MoveType::Castling && !f.alternate() {
+            self.castling_destinations().0
         } else {
-            writeln!(f, "{} → {}", self.from(), self.to())
+            self.to()
+        };
+        write!(f, "{}{}", self.from(), to)?;
+
+        if let Some(promotion) = self.get_promotion() {
+            write!(f, "{}", promotion.with_color(Color::Black).char().unwrap())?;
         }
+
+        Ok(())
+    }
+}
+
+/// Fixed-capacity list of moves living on the stack, filled by the move generator.
+#[derive(Copy, Clone, Debug)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        Self {
+            moves: [Move(0); MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, mv: Move) {
+        debug_assert!(self.len < MAX_MOVES, "Move list overflow");
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &Self::Target {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_from_raw() {
+        let mv = Move::new(Square::E7, Square::D8, MoveType::PromotionCaptureKnight);
+        assert_eq!(Move::try_from(mv.raw()), Ok(mv));
+
+        assert_eq!(Move::try_from(0), Err(InvalidMoveError));
+        let from_to = Square::E2 as u16 | (Square::E4 as u16) << 6;
+        assert_eq!(
+            Move::try_from(from_to | 0b0110 << 12),
+            Err(InvalidMoveError)
+        );
     }
 }
@@ -0,0 +1,174 @@
+use crate::board::Board;
+use crate::moves::{Move, MoveType};
+use crate::piece::Piece;
+use crate::square::Square;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+mod san;
+
+pub use san::SanError;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum UciMoveError {
+    /// Not of the form `<from><to>[promotion]`
+    InvalidFormat,
+    InvalidSquare,
+    InvalidPromotion,
+    /// Well-formed, but not a legal move in the position
+    IllegalMove,
+}
+
+impl Display for UciMoveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UciMoveError::InvalidFormat => write!(f, "expected a move like e2e4 or e7e8q"),
+            UciMoveError::InvalidSquare => write!(f, "invalid square"),
+            UciMoveError::InvalidPromotion => write!(f, "invalid promotion piece"),
+            UciMoveError::IllegalMove => write!(f, "illegal move"),
+        }
+    }
+}
+
+impl Error for UciMoveError {}
+
+impl Board {
+    /// Parses a move in long algebraic notation as used by UCI, e.g. `e2e4` or `e7e8q`. The
+    /// [MoveType] is inferred from the position and the move has to be legal.
+    ///
+    /// Castling is accepted both as the king's move (`e1g1`) and as the king capturing its own
+    /// rook (`e1h1`), as sent by GUIs in Chess960 mode.
+    pub fn parse_uci_move(&self, text: &str) -> Result<Move, UciMoveError> {
+        if !text.is_ascii() || !(4..=5).contains(&text.len()) {
+            return Err(UciMoveError::InvalidFormat);
+        }
+
+        let (Ok(from), Ok(to)) = (text[0..2].parse::<Square>(), text[2..4].parse::<Square>())
+        else {
+            return Err(UciMoveError::InvalidSquare);
+        };
+
+        let promotion = match text[4..].chars().next() {
+            Some(c) => match Piece::try_from(c.to_ascii_uppercase()) {
+                Ok(
+                    piece @ (Piece::WhiteKnight
+                    | Piece::WhiteBishop
+                    | Piece::WhiteRook
+                    | Piece::WhiteQueen),
+                ) => Some(piece),
+                _ => return Err(UciMoveError::InvalidPromotion),
+            },
+            None => None,
+        };
+
+        let mv = Move::new(from, to, self.infer_move_type(from, to, promotion));
+        let moves = self.legal_moves();
+        if moves.contains(&mv) {
+            return Ok(mv);
+        }
+
+        moves
+            .iter()
+            .copied()
+            .find(|castling| {
+                castling.ty() == MoveType::Castling
+                    && castling.from() == from
+                    && castling.castling_destinations().0 == to
+                    && promotion.is_none()
+            })
+            .ok_or(UciMoveError::IllegalMove)
+    }
+
+    fn infer_move_type(&self, from: Square, to: Square, promotion: Option<Piece>) -> MoveType {
+        let piece = self.piece_on(from);
+        let target = self.piece_on(to);
+        let capture = target != Piece::None;
+
+        if let Some(promotion) = promotion {
+            return match (promotion, capture) {
+                (Piece::WhiteKnight, false) => MoveType::PromotionKnight,
+                (Piece::WhiteBishop, false) => MoveType::PromotionBishop,
+                (Piece::WhiteRook, false) => MoveType::PromotionRook,
+                (_, false) => MoveType::PromotionQueen,
+                (Piece::WhiteKnight, true) => MoveType::PromotionCaptureKnight,
+                (Piece::WhiteBishop, true) => MoveType::PromotionCaptureBishop,
+                (Piece::WhiteRook, true) => MoveType::PromotionCaptureRook,
+                (_, true) => MoveType::PromotionCaptureQueen,
+            };
+        }
+
+        let is_pawn = piece == Piece::WhitePawn.with_color(piece.get_color());
+        let is_king = piece == Piece::WhiteKing.with_color(piece.get_color());
+        let own_rook = Piece::WhiteRook.with_color(piece.get_color());
+
+        if is_pawn && to == self.en_passant_square() {
+            MoveType::EnPassant
+        } else if is_pawn && from.rank().abs_diff(to.rank()) == 2 {
+            MoveType::DoublePush
+        } else if is_king && target == own_rook {
+            MoveType::Castling
+        } else if capture {
+            MoveType::Capture
+        } else {
+            MoveType::Normal
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(fen: &str, text: &str) -> Result<MoveType, UciMoveError> {
+        Board::from_fen(fen)
+            .unwrap()
+            .parse_uci_move(text)
+            .map(Move::ty)
+    }
+
+    #[test]
+    fn infers_move_type() {
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+        assert_eq!(parse(kiwipete, "a2a4"), Ok(MoveType::DoublePush));
+        assert_eq!(parse(kiwipete, "a2a3"), Ok(MoveType::Normal));
+        assert_eq!(parse(kiwipete, "e1g1"), Ok(MoveType::Castling));
+        assert_eq!(parse(kiwipete, "e1c1"), Ok(MoveType::Castling));
+        assert_eq!(parse(kiwipete, "e5f7"), Ok(MoveType::Capture));
+        assert_eq!(
+            parse("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"),
+            Ok(MoveType::EnPassant)
+        );
+
+        let promotion = "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(parse(promotion, "a7a8n"), Ok(MoveType::PromotionKnight));
+        assert_eq!(
+            parse(promotion, "a7b8q"),
+            Ok(MoveType::PromotionCaptureQueen)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_moves() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+        assert_eq!(parse(start, "e2"), Err(UciMoveError::InvalidFormat));
+        assert_eq!(parse(start, "e2é4"), Err(UciMoveError::InvalidFormat));
+        assert_eq!(parse(start, "e2e9"), Err(UciMoveError::InvalidSquare));
+        assert_eq!(parse(start, "e2e4k"), Err(UciMoveError::InvalidPromotion));
+        assert_eq!(parse(start, "e2e5"), Err(UciMoveError::IllegalMove));
+        assert_eq!(parse(start, "e7e5"), Err(UciMoveError::IllegalMove));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let mut board = Board::starting_position();
+        for text in [
+            "e2e4", "d7d5", "e4d5", "g8f6", "f1b5", "c7c6", "g1f3", "c6b5",
+        ] {
+            let mv = board.parse_uci_move(text).unwrap();
+            assert_eq!(mv.to_string(), text);
+            board.make_move(mv);
+        }
+    }
+}
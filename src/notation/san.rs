@@ -0,0 +1,263 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::moves::{Move, MoveType};
+use crate::piece::Piece;
+use crate::square::Square;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SanError {
+    InvalidFormat,
+    /// No legal move matches
+    IllegalMove,
+    /// More than one legal move matches, e.g. `Nd2` with knights on b1 and f3
+    AmbiguousMove,
+}
+
+impl Display for SanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SanError::InvalidFormat => write!(f, "expected a move like Nf3, exd5 or O-O"),
+            SanError::IllegalMove => write!(f, "illegal move"),
+            SanError::AmbiguousMove => write!(f, "ambiguous move"),
+        }
+    }
+}
+
+impl Error for SanError {}
+
+impl Board {
+    /// Formats a legal move in [Standard Algebraic Notation](https://www.chessprogramming.org/Algebraic_Chess_Notation#Standard_Algebraic_Notation_.28SAN.29),
+    /// e.g. `Nbd7`, `exd6`, `e8=Q+` or `O-O-O#`.
+    pub fn to_san(&self, mv: Move) -> String {
+        let mut san = String::new();
+        let piece = self.piece_on(mv.from()).with_color(Color::White);
+
+        if mv.ty() == MoveType::Castling {
+            san.push_str(if mv.to().file() > mv.from().file() {
+                "O-O"
+            } else {
+                "O-O-O"
+            });
+        } else {
+            if piece == Piece::WhitePawn {
+                if mv.is_capture() {
+                    san.push(file_char(mv.from()));
+                }
+            } else {
+                san.push(piece.char().unwrap());
+                san.push_str(&self.disambiguation(mv, piece));
+            }
+
+            if mv.is_capture() {
+                san.push('x');
+            }
+            san.push_str(&mv.to().to_string());
+
+            if let Some(promotion) = mv.get_promotion() {
+                san.push('=');
+                san.push(promotion.char().unwrap());
+            }
+        }
+
+        let mut board = *self;
+        board.make_move(mv);
+        if board.in_check() {
+            san.push(if board.legal_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+
+        san
+    }
+
+    /// Parses a move in SAN. Check and annotation suffixes are optional and ignored, `0-0`
+    /// is accepted for castling, pawn moves may start with `P` and the `=` of promotions may be
+    /// omitted (`e8Q`).
+    pub fn parse_san(&self, text: &str) -> Result<Move, SanError> {
+        let text = text.trim_end_matches(['+', '#', '!', '?']);
+
+        let castling = match text {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None,
+        };
+        if let Some(kingside) = castling {
+            return self.find_san_move(|mv| {
+                mv.ty() == MoveType::Castling && (mv.to().file() > mv.from().file()) == kingside
+            });
+        }
+
+        if !text.is_ascii() {
+            return Err(SanError::InvalidFormat);
+        }
+
+        let (piece, rest) = match text.chars().next() {
+            Some(c @ ('N' | 'B' | 'R' | 'Q' | 'K')) => (Piece::try_from(c).unwrap(), &text[1..]),
+            Some('P') => (Piece::WhitePawn, &text[1..]),
+            Some(_) => (Piece::WhitePawn, text),
+            None => return Err(SanError::InvalidFormat),
+        };
+
+        // The destination is the last square in the text, a promotion may follow it
+        let bytes = rest.as_bytes();
+        let to_index = (0..bytes.len().saturating_sub(1))
+            .rev()
+            .find(|&i| is_file(bytes[i]) && is_rank(bytes[i + 1]))
+            .ok_or(SanError::InvalidFormat)?;
+        let to = rest[to_index..to_index + 2].parse::<Square>().unwrap();
+
+        let promotion = match rest[to_index + 2..].trim_start_matches('=').as_bytes() {
+            [] => None,
+            &[c] => match Piece::try_from(c.to_ascii_uppercase() as char) {
+                Ok(
+                    promotion @ (Piece::WhiteKnight
+                    | Piece::WhiteBishop
+                    | Piece::WhiteRook
+                    | Piece::WhiteQueen),
+                ) => Some(promotion),
+                _ => return Err(SanError::InvalidFormat),
+            },
+            _ => return Err(SanError::InvalidFormat),
+        };
+
+        let mut from_file = None;
+        let mut from_rank = None;
+        for &b in &bytes[..to_index] {
+            match b {
+                b'a'..=b'h' => from_file = Some(b - b'a'),
+                b'1'..=b'8' => from_rank = Some(b - b'1'),
+                b'x' | b':' | b'-' => {}
+                _ => return Err(SanError::InvalidFormat),
+            }
+        }
+
+        self.find_san_move(|mv| {
+            mv.ty() != MoveType::Castling
+                && mv.to() == to
+                && self.piece_on(mv.from()).with_color(Color::White) == piece
+                && mv.get_promotion() == promotion
+                && from_file.is_none_or(|file| mv.from().file() == file)
+                && from_rank.is_none_or(|rank| mv.from().rank() == rank)
+        })
+    }
+
+    /// Returns the only legal move matching `filter`.
+    fn find_san_move(&self, filter: impl Fn(Move) -> bool) -> Result<Move, SanError> {
+        let moves = self.legal_moves();
+        let mut matches = moves.iter().copied().filter(|&mv| filter(mv));
+
+        match (matches.next(), matches.next()) {
+            (Some(mv), None) => Ok(mv),
+            (Some(_), Some(_)) => Err(SanError::AmbiguousMove),
+            (None, _) => Err(SanError::IllegalMove),
+        }
+    }
+
+    /// The origin file, rank or square needed to tell `mv` apart from moves of other pieces of
+    /// the same type to the same square.
+    fn disambiguation(&self, mv: Move, piece: Piece) -> String {
+        let others: Vec<Square> = self
+            .legal_moves()
+            .iter()
+            .filter(|other| {
+                other.to() == mv.to()
+                    && other.from() != mv.from()
+                    && self.piece_on(other.from()).with_color(Color::White) == piece
+            })
+            .map(|other| other.from())
+            .collect();
+
+        if others.is_empty() {
+            String::new()
+        } else if others.iter().all(|from| from.file() != mv.from().file()) {
+            file_char(mv.from()).to_string()
+        } else if others.iter().all(|from| from.rank() != mv.from().rank()) {
+            ((b'1' + mv.from().rank()) as char).to_string()
+        } else {
+            mv.from().to_string()
+        }
+    }
+}
+
+fn file_char(square: Square) -> char {
+    (b'a' + square.file()) as char
+}
+
+fn is_file(b: u8) -> bool {
+    (b'a'..=b'h').contains(&b)
+}
+
+fn is_rank(b: u8) -> bool {
+    (b'1'..=b'8').contains(&b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    fn san(fen: &str, uci: &str) -> String {
+        let board = Board::from_fen(fen).unwrap();
+        board.to_san(board.parse_uci_move(uci).unwrap())
+    }
+
+    #[test]
+    fn writes_san() {
+        assert_eq!(san(KIWIPETE, "e1g1"), "O-O");
+        assert_eq!(san(KIWIPETE, "e1c1"), "O-O-O");
+        assert_eq!(san(KIWIPETE, "e5f7"), "Nxf7");
+        assert_eq!(san(KIWIPETE, "d5e6"), "dxe6");
+        assert_eq!(san(KIWIPETE, "f3f6"), "Qxf6");
+        assert_eq!(san(KIWIPETE, "c3b1"), "Nb1");
+        assert_eq!(san("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1", "b1d2"), "Nbd2");
+        assert_eq!(san("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1", "a1d1"), "Rad1");
+        assert_eq!(san("4k3/8/8/8/R7/8/8/R3K3 w - - 0 1", "a1a2"), "R1a2");
+        assert_eq!(san("1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", "h4e1"), "Qh4e1");
+        assert_eq!(san("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q"), "axb8=Q+");
+        assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8#");
+    }
+
+    #[test]
+    fn reads_lenient_san() {
+        let board = Board::from_fen(KIWIPETE).unwrap();
+        let parse = |text| board.parse_san(text).map(|mv| mv.to_string());
+
+        assert_eq!(parse("0-0"), Ok("e1g1".to_string()));
+        assert_eq!(parse("O-O-O"), Ok("e1c1".to_string()));
+        assert_eq!(parse("Qxf6"), Ok("f3f6".to_string()));
+        assert_eq!(parse("Qf3xf6+!?"), Ok("f3f6".to_string()));
+        assert_eq!(parse("d5e6"), Ok("d5e6".to_string()));
+        assert_eq!(parse("Pxe6"), Ok("d5e6".to_string()));
+        assert_eq!(parse("Pg3"), Ok("g2g3".to_string()));
+        assert_eq!(parse("Nd4"), Err(SanError::IllegalMove));
+        assert_eq!(parse("Zd4"), Err(SanError::InvalidFormat));
+
+        let rooks = Board::from_fen("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1").unwrap();
+        assert_eq!(rooks.parse_san("Rd1"), Err(SanError::AmbiguousMove));
+
+        let board = Board::from_fen("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.parse_san("a8Q").unwrap().to_string(), "a7a8q");
+        assert_eq!(board.parse_san("axb8=n").unwrap().to_string(), "a7b8n");
+        assert_eq!(board.parse_san("a8"), Err(SanError::IllegalMove));
+    }
+
+    #[test]
+    fn round_trips_all_legal_moves() {
+        for fen in [
+            KIWIPETE,
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            for &mv in &board.legal_moves() {
+                assert_eq!(board.parse_san(&board.to_san(mv)), Ok(mv), "{fen} {mv}");
+            }
+        }
+    }
+}
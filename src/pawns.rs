@@ -0,0 +1,303 @@
+use crate::bitboard::{Bitboard, pawn_attacks};
+use crate::board::Board;
+use crate::color::Color::{self, Black, White};
+use crate::evaluation::PhaseScore;
+use crate::piece::Piece;
+use crate::square::Square;
+
+const ISOLATED: PhaseScore = PhaseScore::new(-10, -15);
+const DOUBLED: PhaseScore = PhaseScore::new(-10, -25);
+const BACKWARD: PhaseScore = PhaseScore::new(-8, -12);
+
+/// Bonus for a pawn defended by or standing next to a friendly pawn, by relative rank
+const CONNECTED: [PhaseScore; 8] = [
+    PhaseScore::new(0, 0),
+    PhaseScore::new(3, 0),
+    PhaseScore::new(6, 3),
+    PhaseScore::new(10, 6),
+    PhaseScore::new(18, 12),
+    PhaseScore::new(30, 25),
+    PhaseScore::new(50, 40),
+    PhaseScore::new(0, 0),
+];
+
+/// Bonus for a passed pawn by relative rank
+const PASSED: [PhaseScore; 8] = [
+    PhaseScore::new(0, 0),
+    PhaseScore::new(5, 10),
+    PhaseScore::new(5, 15),
+    PhaseScore::new(10, 25),
+    PhaseScore::new(25, 45),
+    PhaseScore::new(50, 80),
+    PhaseScore::new(90, 130),
+    PhaseScore::new(0, 0),
+];
+
+/// Endgame bonus per rank beyond the third for the enemy king's distance to the square in front
+/// of a passed pawn
+const PASSED_ENEMY_KING: i32 = 5;
+/// Endgame penalty per rank beyond the third for our own king's distance to that square
+const PASSED_OWN_KING: i32 = 2;
+
+/// Squares in front of a pawn on its own file, indexed by color and square
+static FRONT_SPANS: [[Bitboard; 64]; 2] = init_spans(false);
+/// Squares in front of a pawn on its own and the adjacent files. A pawn without enemy pawns
+/// there is passed.
+static PASSED_SPANS: [[Bitboard; 64]; 2] = init_spans(true);
+
+const fn init_spans(adjacent_files: bool) -> [[Bitboard; 64]; 2] {
+    let mut spans = [[Bitboard(0); 64]; 2];
+
+    let mut square = 0;
+    while square < 64 {
+        let (file, rank) = (square % 8, square / 8);
+        let mut files = Bitboard::FILE_A.0 << file;
+        if adjacent_files {
+            files |= (files << 1 & !Bitboard::FILE_A.0) | (files >> 1 & !Bitboard::FILE_H.0);
+        }
+
+        let above = if rank == 7 {
+            0
+        } else {
+            u64::MAX << (8 * (rank + 1))
+        };
+        let below = if rank == 0 {
+            0
+        } else {
+            u64::MAX >> (8 * (8 - rank))
+        };
+        spans[White as usize][square] = Bitboard(files & above);
+        spans[Black as usize][square] = Bitboard(files & below);
+        square += 1;
+    }
+
+    spans
+}
+
+/// The files next to the file of `square`
+fn adjacent_files(square: Square) -> Bitboard {
+    let file = Bitboard::FILE_A << square.file() as i32;
+    (file << 1 & !Bitboard::FILE_A) | (file >> 1 & !Bitboard::FILE_H)
+}
+
+/// Rank of `square` as seen from `color`, 0 being its back rank
+fn relative_rank(color: Color, square: Square) -> usize {
+    match color {
+        White => square.rank() as usize,
+        Black => 7 - square.rank() as usize,
+    }
+}
+
+/// Number of king moves between `a` and `b`
+fn distance(a: Square, b: Square) -> i32 {
+    let files = (a.file() as i32 - b.file() as i32).abs();
+    let ranks = (a.rank() as i32 - b.rank() as i32).abs();
+    files.max(ranks)
+}
+
+/// Evaluation of the pawn structure, which only depends on the pawns and is therefore cached
+/// in a [PawnTable]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PawnStructure {
+    key: u64,
+    /// Isolated, doubled, backward and connected pawns, per color from that color's point of view
+    scores: [PhaseScore; 2],
+    passed: [Bitboard; 2],
+}
+
+impl PawnStructure {
+    pub fn new(board: &Board) -> Self {
+        let mut structure = Self {
+            key: board.pawn_hash(),
+            ..Self::default()
+        };
+
+        for color in [White, Black] {
+            let ours = board.pieces(Piece::WhitePawn.with_color(color));
+            let theirs = board.pieces(Piece::WhitePawn.with_color(color.other()));
+            let mut score = PhaseScore::default();
+
+            for square in ours {
+                let rank = relative_rank(color, square);
+                let neighbours = adjacent_files(square) & ours;
+                let supported = pawn_attacks(square, color.other()) & ours;
+                let phalanx = neighbours & (Bitboard::RANK_1 << (8 * square.rank() as i32));
+                let doubled = (FRONT_SPANS[color][square] & ours).is_not_empty();
+
+                if neighbours.is_empty() {
+                    score += ISOLATED;
+                } else if (supported | phalanx).is_not_empty() {
+                    score += CONNECTED[rank];
+                } else {
+                    // All neighbours are ahead and enemy pawns control the square in front
+                    let stop = square.shift(color.pawn_push_offset());
+                    let behind = neighbours & !PASSED_SPANS[color][square];
+                    if behind.is_empty() && (pawn_attacks(stop, color) & theirs).is_not_empty() {
+                        score += BACKWARD;
+                    }
+                }
+
+                if doubled {
+                    score += DOUBLED;
+                } else if (PASSED_SPANS[color][square] & theirs).is_empty() {
+                    structure.passed[color].add(square);
+                }
+            }
+
+            structure.scores[color] = score;
+        }
+
+        structure
+    }
+
+    /// Structural terms of `color` from its point of view, without passed pawns
+    pub fn score(&self, color: Color) -> PhaseScore {
+        self.scores[color]
+    }
+
+    /// Pawns of `color` that no enemy pawn can stop. Only the frontmost of doubled pawns counts.
+    pub fn passed(&self, color: Color) -> Bitboard {
+        self.passed[color]
+    }
+
+    /// Bonus for the passed pawns of `color`, growing with their rank. From the fourth rank on,
+    /// kings close to the square in front of the pawn matter in the endgame. The kings are not
+    /// part of the pawn key, so this is evaluated on every call.
+    pub fn passed_score(&self, board: &Board, color: Color) -> PhaseScore {
+        let our_king = board.king_square(color);
+        let their_king = board.king_square(color.other());
+        let mut score = PhaseScore::default();
+
+        for square in self.passed[color] {
+            let rank = relative_rank(color, square);
+            score += PASSED[rank];
+
+            if rank >= 3 {
+                let stop = square.shift(color.pawn_push_offset());
+                let weight = rank as i32 - 2;
+                score.eg += weight
+                    * (PASSED_ENEMY_KING * distance(stop, their_king)
+                        - PASSED_OWN_KING * distance(stop, our_king));
+            }
+        }
+
+        score
+    }
+}
+
+/// Hash table of [PawnStructure]s indexed by the pawn key of the [Board]. Pawn moves are rare
+/// in the search tree, so almost every probe hits.
+pub struct PawnTable {
+    entries: Vec<PawnStructure>,
+}
+
+impl PawnTable {
+    pub const DEFAULT_ENTRIES: usize = 1 << 14;
+
+    /// Creates a table with `entries` slots, rounded down to a power of two.
+    pub fn new(entries: usize) -> Self {
+        let entries = 1 << entries.max(1).ilog2();
+        Self {
+            entries: vec![PawnStructure::default(); entries],
+        }
+    }
+
+    /// Removes all entries, e.g. before a new game.
+    pub fn clear(&mut self) {
+        self.entries.fill(PawnStructure::default());
+    }
+
+    /// The pawn structure of `board`, computed and stored if it is not in the table yet. Empty
+    /// slots hold the key 0, which is also the key of positions without pawns and matches their
+    /// (empty) structure.
+    pub fn probe(&mut self, board: &Board) -> PawnStructure {
+        let key = board.pawn_hash();
+        let index = key as usize & (self.entries.len() - 1);
+        let entry = &mut self.entries[index];
+        if entry.key != key {
+            *entry = PawnStructure::new(board);
+        }
+        *entry
+    }
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_ENTRIES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn structure(fen: &str) -> PawnStructure {
+        PawnStructure::new(&Board::from_fen(fen).unwrap())
+    }
+
+    fn squares(bitboard: Bitboard) -> Vec<String> {
+        bitboard
+            .into_iter()
+            .map(|square| square.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn passed_pawns() {
+        // d5 is passed, the rear of the doubled b-pawns and the blocked h-pawn are not
+        let pawns = structure("4k3/7p/8/3P4/1P6/1P5P/8/4K3 w - - 0 1");
+        assert_eq!(squares(pawns.passed(White)), ["b4", "d5"]);
+        assert_eq!(squares(pawns.passed(Black)), Vec::<String>::new());
+
+        let pawns = structure("4k3/8/8/8/8/p7/8/4K3 w - - 0 1");
+        assert_eq!(squares(pawns.passed(Black)), ["a3"]);
+    }
+
+    #[test]
+    fn structural_terms() {
+        let score = |fen| structure(fen).score(White);
+
+        assert_eq!(score("4k3/8/8/8/8/8/3P4/4K3 w - - 0 1"), ISOLATED);
+        assert_eq!(
+            score("4k3/8/8/8/8/3P4/3P4/4K3 w - - 0 1"),
+            ISOLATED + ISOLATED + DOUBLED
+        );
+        // e4 defended by d3, which is backward against the c5 pawn
+        assert_eq!(
+            score("4k3/8/8/2p5/4P3/3P4/8/4K3 w - - 0 1"),
+            CONNECTED[3] + BACKWARD
+        );
+        // Phalanx
+        assert_eq!(
+            score("4k3/8/8/8/3PP3/8/8/4K3 w - - 0 1"),
+            CONNECTED[3] + CONNECTED[3]
+        );
+    }
+
+    #[test]
+    fn king_proximity() {
+        let passed = |fen| {
+            let board = Board::from_fen(fen).unwrap();
+            PawnStructure::new(&board).passed_score(&board, White)
+        };
+
+        let escorted = passed("8/k7/8/3PK3/8/8/8/8 w - - 0 1");
+        let caught = passed("8/8/3k4/3P4/8/8/8/7K w - - 0 1");
+        assert_eq!(escorted.mg, caught.mg);
+        assert!(escorted.eg > caught.eg);
+    }
+
+    #[test]
+    fn table_matches_fresh_computation() {
+        let mut table = PawnTable::new(16);
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(table.probe(&board), PawnStructure::new(&board), "{fen}");
+            assert_eq!(table.probe(&board), PawnStructure::new(&board), "{fen}");
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::board::Board;
+use crate::moves::Move;
+
+impl Board {
+    /// Counts the leaf nodes of the legal move tree up to `depth`, see
+    /// [here](https://www.chessprogramming.org/Perft).
+    pub fn perft(&mut self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.legal_moves();
+        // Bulk counting: the number of legal moves is the number of leaves
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for &mv in &moves {
+            let undo = self.make_move(mv);
+            nodes += self.perft(depth - 1);
+            self.unmake_move(mv, undo);
+        }
+
+        nodes
+    }
+
+    /// Like [Board::perft], but returns the node count below every root move. Empty at depth
+    /// 0, where the position itself is the only leaf.
+    pub fn perft_divide(&mut self, depth: usize) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+
+        self.legal_moves()
+            .iter()
+            .map(|&mv| {
+                let undo = self.make_move(mv);
+                let nodes = self.perft(depth - 1);
+                self.unmake_move(mv, undo);
+                (mv, nodes)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn perft(fen: &str, depth: usize) -> u64 {
+        Board::from_fen(fen).unwrap().perft(depth)
+    }
+
+    #[test]
+    fn perft_starting_position() {
+        let mut board = Board::starting_position();
+
+        assert_eq!(board.perft(1), 20);
+        assert_eq!(board.perft(2), 400);
+        assert_eq!(board.perft(3), 8_902);
+        assert_eq!(board.perft(4), 197_281);
+    }
+
+    #[test]
+    fn perft_divide() {
+        let mut board = Board::starting_position();
+
+        let divide = board.perft_divide(3);
+        assert_eq!(divide.len(), 20);
+        assert_eq!(divide.iter().map(|&(_, nodes)| nodes).sum::<u64>(), 8_902);
+        assert!(divide.iter().all(|&(_, nodes)| nodes > 0));
+        assert!(board.perft_divide(0).is_empty());
+    }
+
+    #[test]
+    fn perft_chess960() {
+        let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+        assert_eq!(perft(fen, 1), 21);
+        assert_eq!(perft(fen, 2), 528);
+        assert_eq!(perft(fen, 3), 12_189);
+
+        let fen = "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9";
+        assert_eq!(perft(fen, 3), 18_002);
+
+        let fen = "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9";
+        assert_eq!(perft(fen, 3), 10_471);
+    }
+
+    #[test]
+    fn perft_kiwipete() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+        assert_eq!(perft(fen, 1), 48);
+        assert_eq!(perft(fen, 2), 2_039);
+        assert_eq!(perft(fen, 3), 97_862);
+    }
+
+    #[test]
+    fn perft_position_3() {
+        assert_eq!(
+            perft("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4),
+            43_238
+        );
+    }
+
+    #[test]
+    fn perft_position_4() {
+        let fen = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+
+        assert_eq!(perft(fen, 3), 9_467);
+    }
+
+    #[test]
+    fn perft_position_5() {
+        let fen = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+
+        assert_eq!(perft(fen, 3), 62_379);
+    }
+
+    #[test]
+    fn perft_position_6() {
+        let fen = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+        assert_eq!(perft(fen, 3), 89_890);
+    }
+}
@@ -0,0 +1,395 @@
+use crate::board::Board;
+use crate::color::Color;
+use crate::moves::Move;
+use std::fmt::{Display, Formatter};
+
+mod reader;
+
+pub use reader::{PgnError, PgnErrorKind, PgnReader};
+
+/// Index of a [Node] in its [PgnGame]
+pub type NodeId = usize;
+
+/// Tags every PGN game has to contain, in export order, with their values if unknown
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+/// Maximum length of a movetext line in exported PGN
+const LINE_WIDTH: usize = 79;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// Game still in progress, abandoned or result unknown
+    Unknown,
+}
+
+impl GameResult {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "1-0" => Some(GameResult::WhiteWins),
+            "0-1" => Some(GameResult::BlackWins),
+            "1/2-1/2" => Some(GameResult::Draw),
+            "*" => Some(GameResult::Unknown),
+            _ => None,
+        }
+    }
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameResult::WhiteWins => write!(f, "1-0"),
+            GameResult::BlackWins => write!(f, "0-1"),
+            GameResult::Draw => write!(f, "1/2-1/2"),
+            GameResult::Unknown => write!(f, "*"),
+        }
+    }
+}
+
+/// A position in the game tree together with the move leading to it.
+#[derive(Clone, Debug)]
+pub struct Node {
+    /// Position after `mv`
+    pub board: Board,
+    /// [None] for the root
+    pub mv: Option<Move>,
+    pub parent: Option<NodeId>,
+    /// The first child continues the main line, the others are variations
+    pub children: Vec<NodeId>,
+    /// [Numeric Annotation Glyphs](https://en.wikipedia.org/wiki/Numeric_Annotation_Glyphs)
+    pub nags: Vec<u8>,
+    /// Comments in front of the move, e.g. at the start of a variation
+    pub starting_comments: Vec<String>,
+    /// Comments after the move. For the root these are the comments before the first move.
+    pub comments: Vec<String>,
+}
+
+impl Node {
+    fn new(board: Board, mv: Option<Move>, parent: Option<NodeId>) -> Self {
+        Self {
+            board,
+            mv,
+            parent,
+            children: Vec::new(),
+            nags: Vec::new(),
+            starting_comments: Vec::new(),
+            comments: Vec::new(),
+        }
+    }
+}
+
+/// A game with its tag pairs and a tree of moves including variations, see the
+/// [PGN specification](https://www.saremba.de/chessgml/standards/pgn/pgn-complete.htm).
+///
+/// [Display] writes the game in PGN export format.
+#[derive(Clone, Debug)]
+pub struct PgnGame {
+    /// Tag pairs in the order they were read or set
+    pub tags: Vec<(String, String)>,
+    pub result: GameResult,
+    nodes: Vec<Node>,
+}
+
+impl PgnGame {
+    pub const ROOT: NodeId = 0;
+
+    /// Creates a game without moves. Non-standard starting positions get `SetUp` and `FEN`
+    /// tags, and a `Variant` tag if the castling rights are only valid in Chess960.
+    pub fn new(board: Board) -> Self {
+        let mut game = Self {
+            tags: Vec::new(),
+            result: GameResult::Unknown,
+            nodes: vec![Node::new(board, None, None)],
+        };
+
+        if !board.castling_rights().is_standard() {
+            game.set_tag("Variant", "Chess960");
+        }
+        if board != Board::starting_position() {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &board.fen());
+        }
+
+        game
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id]
+    }
+
+    /// Plays the legal move `mv` in the position of `parent` and returns the resulting node.
+    /// The first move added to a node continues its main line, later ones start variations.
+    pub fn add_move(&mut self, parent: NodeId, mv: Move) -> NodeId {
+        if let Some(&existing) = self.nodes[parent]
+            .children
+            .iter()
+            .find(|&&child| self.nodes[child].mv == Some(mv))
+        {
+            return existing;
+        }
+
+        let mut board = self.nodes[parent].board;
+        board.make_move(mv);
+
+        let id = self.nodes.len();
+        self.nodes.push(Node::new(board, Some(mv), Some(parent)));
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    /// Nodes of the main line, starting at the root
+    pub fn mainline(&self) -> impl Iterator<Item = &Node> {
+        std::iter::successors(Some(&self.nodes[Self::ROOT]), |node| {
+            node.children.first().map(|&child| &self.nodes[child])
+        })
+    }
+
+    pub fn mainline_moves(&self) -> Vec<Move> {
+        self.mainline().filter_map(|node| node.mv).collect()
+    }
+
+    /// Movetext split into tokens that may be separated by line breaks
+    fn movetext(&self) -> Vec<String> {
+        let mut tokens = Vec::new();
+        push_comments(&mut tokens, &self.nodes[Self::ROOT].comments);
+        self.push_line(&mut tokens, Self::ROOT, true);
+        tokens.push(self.result.to_string());
+        tokens
+    }
+
+    /// Pushes the main line below `start` including all variations branching off it.
+    fn push_line(&self, tokens: &mut Vec<String>, start: NodeId, mut force_number: bool) {
+        let mut current = start;
+
+        while let Some((&main, variations)) = self.nodes[current].children.split_first() {
+            force_number = self.push_move(tokens, main, force_number);
+
+            for &variation in variations {
+                let first = tokens.len();
+                let force_variation_number = self.push_move(tokens, variation, true);
+                self.push_line(tokens, variation, force_variation_number);
+
+                tokens[first].insert(0, '(');
+                tokens.last_mut().unwrap().push(')');
+                force_number = true;
+            }
+
+            current = main;
+        }
+    }
+
+    /// Pushes a single move with its number, annotations and comments. Returns whether the
+    /// next move needs a move number even if black is to move.
+    fn push_move(&self, tokens: &mut Vec<String>, id: NodeId, force_number: bool) -> bool {
+        let node = &self.nodes[id];
+        let parent = &self.nodes[node.parent.unwrap()].board;
+
+        push_comments(tokens, &node.starting_comments);
+
+        // The move number is kept on the same line as the move
+        let san = parent.to_san(node.mv.unwrap());
+        tokens.push(match parent.side_to_move() {
+            Color::White => format!("{}. {san}", parent.full_moves()),
+            Color::Black if force_number || !node.starting_comments.is_empty() => {
+                format!("{}... {san}", parent.full_moves())
+            }
+            Color::Black => san,
+        });
+
+        tokens.extend(node.nags.iter().map(|nag| format!("${nag}")));
+        push_comments(tokens, &node.comments);
+
+        !node.comments.is_empty()
+    }
+}
+
+impl Display for PgnGame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (name, default) in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => self.result.to_string(),
+                _ => self.tag(name).unwrap_or(default).to_string(),
+            };
+            writeln!(f, "[{name} \"{}\"]", escape(&value))?;
+        }
+
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.iter().any(|(roster, _)| roster == name) {
+                writeln!(f, "[{name} \"{}\"]", escape(value))?;
+            }
+        }
+
+        writeln!(f)?;
+
+        let mut line = String::new();
+        for token in self.movetext() {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                writeln!(f, "{line}")?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+
+        writeln!(f, "{line}")
+    }
+}
+
+/// Pushes comments word by word, so long comments can be wrapped
+fn push_comments(tokens: &mut Vec<String>, comments: &[String]) {
+    for comment in comments {
+        let mut words: Vec<String> = comment.split_whitespace().map(String::from).collect();
+        if words.is_empty() {
+            words.push(String::new());
+        }
+
+        words.first_mut().unwrap().insert(0, '{');
+        words.last_mut().unwrap().push('}');
+        tokens.extend(words);
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAME: &str = r#"[Event "F/S Return Match"]
+[Site "Belgrade, Serbia JUG"]
+[Date "1992.11.04"]
+[Round "29"]
+[White "Fischer, Robert J."]
+[Black "Spassky, Boris V."]
+[Result "1/2-1/2"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 {This opening is called the Ruy Lopez.} 3... a6
+4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
+11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15. Nb1 h6 16. Bh4 c5 17. dxe5
+Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21. Nc4 Nxc4 22. Bxc4 Nb6 23. Ne5
+Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7 27. Qe3 Qg5 28. Qxg5 hxg5
+29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33. f3 Bc8 34. Kf2 Bf5 35. Ra7
+g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5 40. Rd6 Kc5 41. Ra6 Nf2
+42. g4 Bd3 43. Re6 1/2-1/2
+"#;
+
+    fn read(pgn: &str) -> PgnGame {
+        PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap()
+    }
+
+    #[test]
+    fn writes_what_it_reads() {
+        let game = read(GAME);
+
+        assert_eq!(game.tag("White"), Some("Fischer, Robert J."));
+        assert_eq!(game.result, GameResult::Draw);
+        assert_eq!(game.mainline_moves().len(), 85);
+        assert_eq!(game.to_string(), GAME);
+        assert!(GAME.lines().all(|line| line.len() <= LINE_WIDTH));
+    }
+
+    #[test]
+    fn variations_comments_and_nags() {
+        let pgn = r#"[Event "?"]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "?"]
+[Black "?"]
+[Result "*"]
+[Annotator "Someone \"quoted\""]
+
+{Game comment} 1. e4 $1 (1. d4 d5 (1... Nf6 2. c4) 2. c4 {Queen's Gambit})
+(1. c4) 1... e5 *
+"#;
+        let game = read(pgn);
+
+        assert_eq!(game.tag("Annotator"), Some("Someone \"quoted\""));
+        assert_eq!(game.node(PgnGame::ROOT).comments, ["Game comment"]);
+
+        let root = game.node(PgnGame::ROOT);
+        assert_eq!(root.children.len(), 3);
+
+        let e4 = game.node(root.children[0]);
+        assert_eq!(e4.nags, [1]);
+
+        let d4 = game.node(root.children[1]);
+        let d5 = game.node(d4.children[0]);
+        assert_eq!(d4.children.len(), 2);
+        assert_eq!(game.node(d5.children[0]).comments, ["Queen's Gambit"]);
+
+        assert_eq!(game.to_string(), pgn);
+    }
+
+    #[test]
+    fn builds_games() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1").unwrap();
+        let mut game = PgnGame::new(board);
+
+        let mut node = PgnGame::ROOT;
+        for san in ["Kd7", "e4", "Ke6"] {
+            let mv = game.node(node).board.parse_san(san).unwrap();
+            node = game.add_move(node, mv);
+        }
+        game.set_tag("White", "Croissant");
+
+        let pgn = game.to_string();
+        assert!(pgn.contains("[White \"Croissant\"]"));
+        assert!(pgn.contains("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 1\"]"));
+        assert!(pgn.ends_with("\n\n1... Kd7 2. e4 Ke6 *\n"));
+
+        assert_eq!(read(&pgn).mainline_moves(), game.mainline_moves());
+    }
+
+    #[test]
+    fn round_trips_chess960() {
+        let fen = "rknnbbqr/pppppppp/8/8/8/8/PPPPPPPP/RKNNBBQR w KQkq - 0 1";
+        let mut game = PgnGame::new(Board::from_fen_chess960(fen).unwrap());
+
+        let mut node = PgnGame::ROOT;
+        for san in ["Nb3", "Nb6", "Nc3", "Nc6", "O-O-O", "O-O-O"] {
+            let mv = game.node(node).board.parse_san(san).unwrap();
+            node = game.add_move(node, mv);
+        }
+
+        let pgn = game.to_string();
+        assert!(pgn.contains("[Variant \"Chess960\"]"));
+        assert!(pgn.contains(&format!("[FEN \"{fen}\"]")));
+
+        let read = read(&pgn);
+        assert_eq!(read.mainline_moves(), game.mainline_moves());
+        assert_eq!(read.to_string(), pgn);
+    }
+}
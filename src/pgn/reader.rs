@@ -0,0 +1,581 @@
+use crate::board::{Board, FenError};
+use crate::notation::SanError;
+use crate::pgn::{GameResult, NodeId, PgnGame};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+
+/// Deepest nesting of variations accepted, which keeps hostile input from overflowing the stack
+const MAX_VARIATION_DEPTH: usize = 64;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PgnErrorKind {
+    Io(std::io::ErrorKind),
+    UnexpectedChar(char),
+    UnexpectedToken(String),
+    UnterminatedString,
+    UnterminatedComment,
+    UnterminatedVariation,
+    VariationTooDeep,
+    /// `--`, which some programs write for a null move
+    NullMove,
+    InvalidNag(String),
+    InvalidFen(FenError),
+    InvalidMove {
+        san: String,
+        error: SanError,
+    },
+}
+
+/// Error while reading PGN, located at a 1-based line and column (in bytes)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PgnError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: PgnErrorKind,
+}
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+
+        match &self.kind {
+            PgnErrorKind::Io(kind) => write!(f, "{kind}"),
+            PgnErrorKind::UnexpectedChar(c) => write!(f, "unexpected character '{c}'"),
+            PgnErrorKind::UnexpectedToken(token) => write!(f, "unexpected '{token}'"),
+            PgnErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            PgnErrorKind::UnterminatedComment => write!(f, "unterminated comment"),
+            PgnErrorKind::UnterminatedVariation => write!(f, "unterminated variation"),
+            PgnErrorKind::VariationTooDeep => {
+                write!(f, "variations nested deeper than {MAX_VARIATION_DEPTH}")
+            }
+            PgnErrorKind::NullMove => write!(f, "null moves are not supported"),
+            PgnErrorKind::InvalidNag(nag) => write!(f, "invalid annotation {nag}"),
+            PgnErrorKind::InvalidFen(error) => write!(f, "invalid FEN: {error}"),
+            PgnErrorKind::InvalidMove { san, error } => write!(f, "{san}: {error}"),
+        }
+    }
+}
+
+impl Error for PgnError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    OpenBracket,
+    CloseBracket,
+    OpenParen,
+    CloseParen,
+    Period,
+    Asterisk,
+    String(String),
+    /// Move, move number, tag name or result
+    Symbol(String),
+    Nag(u8),
+    Comment(String),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::OpenBracket => write!(f, "["),
+            Token::CloseBracket => write!(f, "]"),
+            Token::OpenParen => write!(f, "("),
+            Token::CloseParen => write!(f, ")"),
+            Token::Period => write!(f, "."),
+            Token::Asterisk => write!(f, "*"),
+            Token::String(string) => write!(f, "\"{string}\""),
+            Token::Symbol(symbol) => write!(f, "{symbol}"),
+            Token::Nag(nag) => write!(f, "${nag}"),
+            Token::Comment(comment) => write!(f, "{{{comment}}}"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    fn error(self, kind: PgnErrorKind) -> PgnError {
+        PgnError {
+            line: self.line,
+            column: self.column,
+            kind,
+        }
+    }
+}
+
+/// Splits PGN into tokens, reading the input one line at a time.
+struct Lexer<R> {
+    reader: R,
+    line: Vec<u8>,
+    index: usize,
+    line_number: usize,
+    peeked: Option<(Token, Position)>,
+}
+
+impl<R: BufRead> Lexer<R> {
+    fn position(&self) -> Position {
+        Position {
+            line: self.line_number,
+            column: self.index + 1,
+        }
+    }
+
+    /// Reads the next line if the current one is exhausted. Returns false at the end of the
+    /// input.
+    fn fill(&mut self) -> Result<bool, PgnError> {
+        while self.index >= self.line.len() {
+            self.line.clear();
+            self.index = 0;
+
+            let read = self
+                .reader
+                .read_until(b'\n', &mut self.line)
+                .map_err(|error| self.position().error(PgnErrorKind::Io(error.kind())))?;
+            if read == 0 {
+                return Ok(false);
+            }
+            self.line_number += 1;
+
+            // UTF-8 byte order mark
+            if self.line_number == 1 && self.line.starts_with(b"\xEF\xBB\xBF") {
+                self.index = 3;
+            }
+
+            // Lines starting with % are escaped from PGN processing
+            if self.line[0] == b'%' {
+                self.index = self.line.len();
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn peek_byte(&mut self) -> Result<Option<u8>, PgnError> {
+        Ok(self.fill()?.then(|| self.line[self.index]))
+    }
+
+    fn peek(&mut self) -> Result<Option<&(Token, Position)>, PgnError> {
+        if self.peeked.is_none() {
+            self.peeked = self.read_token()?;
+        }
+
+        Ok(self.peeked.as_ref())
+    }
+
+    fn next(&mut self) -> Result<Option<(Token, Position)>, PgnError> {
+        match self.peeked.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.read_token(),
+        }
+    }
+
+    fn read_token(&mut self) -> Result<Option<(Token, Position)>, PgnError> {
+        loop {
+            let Some(b) = self.peek_byte()? else {
+                return Ok(None);
+            };
+
+            if b.is_ascii_whitespace() {
+                self.index += 1;
+            } else if b == b';' {
+                // Rest of line comment
+                self.index = self.line.len();
+            } else {
+                break;
+            }
+        }
+
+        let position = self.position();
+        let b = self.line[self.index];
+        self.index += 1;
+
+        let token =
+            match b {
+                b'[' => Token::OpenBracket,
+                b']' => Token::CloseBracket,
+                b'(' => Token::OpenParen,
+                b')' => Token::CloseParen,
+                b'.' => Token::Period,
+                b'*' => Token::Asterisk,
+                b'-' if self.line.get(self.index) == Some(&b'-') => {
+                    self.index += 1;
+                    Token::Symbol("--".to_string())
+                }
+                b'"' => Token::String(self.read_string(position)?),
+                b'{' => Token::Comment(self.read_comment(position)?),
+                b'$' => {
+                    let digits = self.take_while(|b| b.is_ascii_digit());
+                    Token::Nag(digits.parse().map_err(|_| {
+                        position.error(PgnErrorKind::InvalidNag(format!("${digits}")))
+                    })?)
+                }
+                b'!' | b'?' => {
+                    self.index -= 1;
+                    let suffix = self.take_while(|b| b == b'!' || b == b'?');
+                    Token::Nag(match suffix.as_str() {
+                        "!" => 1,
+                        "?" => 2,
+                        "!!" => 3,
+                        "??" => 4,
+                        "!?" => 5,
+                        "?!" => 6,
+                        _ => return Err(position.error(PgnErrorKind::InvalidNag(suffix))),
+                    })
+                }
+                _ if b.is_ascii_alphanumeric() => {
+                    self.index -= 1;
+                    Token::Symbol(
+                        self.take_while(|b| b.is_ascii_alphanumeric() || b"_+#=:-/".contains(&b)),
+                    )
+                }
+                _ => {
+                    let c = String::from_utf8_lossy(&self.line[self.index - 1..])
+                        .chars()
+                        .next()
+                        .unwrap();
+                    return Err(position.error(PgnErrorKind::UnexpectedChar(c)));
+                }
+            };
+
+        Ok(Some((token, position)))
+    }
+
+    /// Consumes bytes of the current line while `predicate` holds
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> String {
+        let start = self.index;
+        while self.index < self.line.len() && predicate(self.line[self.index]) {
+            self.index += 1;
+        }
+
+        String::from_utf8_lossy(&self.line[start..self.index]).into_owned()
+    }
+
+    /// Reads a string up to the closing quote, which has to be on the same line.
+    fn read_string(&mut self, start: Position) -> Result<String, PgnError> {
+        let mut string = Vec::new();
+
+        loop {
+            let Some(&b) = self.line.get(self.index) else {
+                return Err(start.error(PgnErrorKind::UnterminatedString));
+            };
+            self.index += 1;
+
+            match b {
+                b'"' => return Ok(String::from_utf8_lossy(&string).into_owned()),
+                b'\\' if matches!(self.line.get(self.index), Some(b'"' | b'\\')) => {
+                    string.push(self.line[self.index]);
+                    self.index += 1;
+                }
+                b'\n' | b'\r' => return Err(start.error(PgnErrorKind::UnterminatedString)),
+                _ => string.push(b),
+            }
+        }
+    }
+
+    /// Reads a comment up to the closing brace, possibly spanning several lines. Line breaks
+    /// are replaced by spaces.
+    fn read_comment(&mut self, start: Position) -> Result<String, PgnError> {
+        let mut comment = Vec::new();
+
+        loop {
+            let Some(b) = self.peek_byte()? else {
+                return Err(start.error(PgnErrorKind::UnterminatedComment));
+            };
+            self.index += 1;
+
+            match b {
+                b'}' => break,
+                b'\r' | b'\n' => comment.push(b' '),
+                _ => comment.push(b),
+            }
+        }
+
+        let comment = String::from_utf8_lossy(&comment);
+        Ok(comment.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    /// Skips the rest of a broken game: everything up to the next tag section following an
+    /// empty line.
+    fn skip_to_next_game(&mut self) -> Result<(), PgnError> {
+        self.peeked = None;
+        self.index = self.line.len();
+
+        let mut empty_line = false;
+        while self.fill()? {
+            let line = self.line.trim_ascii();
+            if empty_line && line.first() == Some(&b'[') {
+                return Ok(());
+            }
+
+            empty_line = line.is_empty();
+            self.index = self.line.len();
+        }
+
+        Ok(())
+    }
+}
+
+/// Streams games from PGN input, e.g. a [std::io::BufReader] over a file, reading only one
+/// game at a time.
+///
+/// After an error the reader skips to the next game, so iteration can continue.
+pub struct PgnReader<R> {
+    lexer: Lexer<R>,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lexer: Lexer {
+                reader,
+                line: Vec::new(),
+                index: 0,
+                line_number: 0,
+                peeked: None,
+            },
+        }
+    }
+
+    fn read_game(&mut self) -> Result<Option<PgnGame>, PgnError> {
+        if self.lexer.peek()?.is_none() {
+            return Ok(None);
+        }
+
+        let mut tags = Vec::new();
+        let mut fen = None;
+        while let Some((Token::OpenBracket, _)) = self.lexer.peek()? {
+            self.lexer.next()?;
+            let name = match self.expect_token()? {
+                (Token::Symbol(name), _) => name,
+                (token, position) => return Err(unexpected(token, position)),
+            };
+            let (value, position) = match self.expect_token()? {
+                (Token::String(value), position) => (value, position),
+                (token, position) => return Err(unexpected(token, position)),
+            };
+            match self.expect_token()? {
+                (Token::CloseBracket, _) => {}
+                (token, position) => return Err(unexpected(token, position)),
+            }
+
+            if name == "FEN" {
+                fen = Some((value.clone(), position));
+            }
+            tags.push((name, value));
+        }
+
+        // Chess960 games may use X-FEN castling rights
+        let chess960 = tags.iter().any(|(name, value)| {
+            name == "Variant"
+                && ["chess960", "chess 960", "fischerandom"]
+                    .contains(&value.to_lowercase().as_str())
+        });
+        let board = match fen {
+            Some((fen, position)) => {
+                let board = if chess960 {
+                    Board::from_fen_chess960(&fen)
+                } else {
+                    Board::from_fen(&fen)
+                };
+                board.map_err(|error| position.error(PgnErrorKind::InvalidFen(error)))?
+            }
+            None => Board::starting_position(),
+        };
+
+        let mut game = PgnGame::new(board);
+        game.tags = tags;
+
+        let tag_result = game.tag("Result").and_then(GameResult::parse);
+        let result = self.read_movetext(&mut game, PgnGame::ROOT, 0)?;
+        game.result = result.or(tag_result).unwrap_or(GameResult::Unknown);
+
+        Ok(Some(game))
+    }
+
+    /// Reads moves starting at `start` until the end of the variation or game. Returns the
+    /// game termination marker, if any.
+    fn read_movetext(
+        &mut self,
+        game: &mut PgnGame,
+        start: NodeId,
+        depth: usize,
+    ) -> Result<Option<GameResult>, PgnError> {
+        let mut current = start;
+        let mut has_move = false;
+        let mut pending_comments = Vec::new();
+
+        loop {
+            match self.lexer.peek()? {
+                None if depth == 0 => return Ok(None),
+                None => {
+                    let position = self.lexer.position();
+                    return Err(position.error(PgnErrorKind::UnterminatedVariation));
+                }
+                // The next game starts without a termination marker in this one
+                Some((Token::OpenBracket, _)) if depth == 0 => return Ok(None),
+                _ => {}
+            }
+
+            let (token, position) = self.lexer.next()?.unwrap();
+            match token {
+                // Results inside variations are ignored
+                Token::Asterisk if depth == 0 => return Ok(Some(GameResult::Unknown)),
+                Token::Asterisk => {}
+                Token::Symbol(symbol) if GameResult::parse(&symbol).is_some() => {
+                    if depth == 0 {
+                        return Ok(GameResult::parse(&symbol));
+                    }
+                }
+                Token::Symbol(symbol) if symbol == "--" => {
+                    return Err(position.error(PgnErrorKind::NullMove));
+                }
+                // Move numbers
+                Token::Symbol(symbol) if symbol.bytes().all(|b| b.is_ascii_digit()) => {}
+                Token::Period => {}
+                Token::Symbol(san) => {
+                    let mv = game.node(current).board.parse_san(&san).map_err(|error| {
+                        position.error(PgnErrorKind::InvalidMove {
+                            san: san.clone(),
+                            error,
+                        })
+                    })?;
+
+                    current = game.add_move(current, mv);
+                    game.node_mut(current)
+                        .starting_comments
+                        .append(&mut pending_comments);
+                    has_move = true;
+                }
+                Token::Nag(nag) => game.node_mut(current).nags.push(nag),
+                Token::Comment(comment) if has_move || depth == 0 => {
+                    game.node_mut(current).comments.push(comment)
+                }
+                Token::Comment(comment) => pending_comments.push(comment),
+                Token::OpenParen if depth >= MAX_VARIATION_DEPTH => {
+                    return Err(position.error(PgnErrorKind::VariationTooDeep));
+                }
+                Token::OpenParen if has_move => {
+                    let parent = game.node(current).parent.unwrap();
+                    self.read_movetext(game, parent, depth + 1)?;
+                }
+                Token::CloseParen if depth > 0 => return Ok(None),
+                token => return Err(unexpected(token, position)),
+            }
+        }
+    }
+
+    fn expect_token(&mut self) -> Result<(Token, Position), PgnError> {
+        let position = self.lexer.position();
+        self.lexer
+            .next()?
+            .ok_or_else(|| position.error(PgnErrorKind::Io(std::io::ErrorKind::UnexpectedEof)))
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_game() {
+            Ok(game) => game.map(Ok),
+            Err(error) => {
+                // A failing reader has nothing more to give
+                if self.lexer.skip_to_next_game().is_err() {
+                    self.lexer.index = self.lexer.line.len();
+                }
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+fn unexpected(token: Token, position: Position) -> PgnError {
+    position.error(PgnErrorKind::UnexpectedToken(token.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_errors_and_recovers() {
+        let pgn = "[Event \"First\"]\n\n1. e4 e5 2. Nf3 Nf6 3. Ke3 *\n\n\
+                   [Event \"Second\"]\n\n1. d4 {unterminated\n\n\
+                   [Event \"Third\"]\n\n1. c4 1-0\n";
+        let mut reader = PgnReader::new(pgn.as_bytes());
+
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!((error.line, error.column), (3, 24));
+        assert_eq!(
+            error.kind,
+            PgnErrorKind::InvalidMove {
+                san: "Ke3".to_string(),
+                error: SanError::IllegalMove
+            }
+        );
+
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!((error.line, error.column), (7, 7));
+        assert_eq!(error.kind, PgnErrorKind::UnterminatedComment);
+
+        // The unterminated comment swallowed the third game
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn real_world_quirks() {
+        let pgn = "\u{feff}[Event \"A\"]\n1. e4 (1. d4 d5 *) e5 *\n\n\
+                   [Event \"B\"]\n\n1. e4 -- 2. d4 *\n\n\
+                   [Event \"C\"]\n\n1. c4 *\n";
+        let mut reader = PgnReader::new(pgn.as_bytes());
+
+        let game = reader.next().unwrap().unwrap();
+        assert_eq!(game.tag("Event"), Some("A"));
+        assert_eq!(game.mainline_moves().len(), 2);
+        assert_eq!(game.result, GameResult::Unknown);
+
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!((error.line, error.column), (6, 7));
+        assert_eq!(error.kind, PgnErrorKind::NullMove);
+
+        let game = reader.next().unwrap().unwrap();
+        assert_eq!(game.tag("Event"), Some("C"));
+    }
+
+    #[test]
+    fn chess960_variant() {
+        let fen = "[FEN \"bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1\"]";
+        let pgn = format!("[Variant \"Chess960\"]\n{fen}\n\n1. g3 *\n\n{fen}\n\n1. g3 *\n");
+        let mut reader = PgnReader::new(pgn.as_bytes());
+
+        assert!(reader.next().unwrap().is_ok());
+        let error = reader.next().unwrap().unwrap_err();
+        assert!(matches!(error.kind, PgnErrorKind::InvalidFen(_)));
+    }
+
+    #[test]
+    fn limits_variation_depth() {
+        let pgn = format!("1. e4 {} *", "(1. d4 ".repeat(10_000));
+        let error = PgnReader::new(pgn.as_bytes()).next().unwrap().unwrap_err();
+        assert_eq!(error.kind, PgnErrorKind::VariationTooDeep);
+    }
+
+    #[test]
+    fn reads_multiple_games() {
+        let pgn =
+            "[Event \"A\"]\n1. e4 e5 1-0\n[Event \"B\"]\n% escaped\n1. d4 ; rest of line\nd5 0-1";
+        let games: Vec<PgnGame> = PgnReader::new(pgn.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tag("Event"), Some("A"));
+        assert_eq!(games[0].result, GameResult::WhiteWins);
+        assert_eq!(games[1].mainline_moves().len(), 2);
+        assert_eq!(games[1].result, GameResult::BlackWins);
+    }
+}
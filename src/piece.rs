@@ -1,6 +1,8 @@
 use crate::color::Color;
-use std::fmt::Display;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::ops::{Index, IndexMut};
+use std::str::FromStr;
 
 /// Same implementation as used by [Reckless][R]
 ///
@@ -25,15 +27,17 @@ pub enum Piece {
 }
 
 impl Piece {
-    pub fn new(value: u8) -> Self {
+    /// Unchecked conversion for trusted values, use [Piece::try_from] for untrusted input
+    pub(crate) fn new(value: u8) -> Self {
         debug_assert!(value < 13);
         unsafe { std::mem::transmute(value) }
     }
 
+    /// The same piece type in `color`, [Piece::None] stays [Piece::None].
     pub fn with_color(self, color: Color) -> Self {
         Self::new(match color {
             Color::White => (self as u8) & 0b11111110,
-            Color::Black => (self as u8) | 1,
+            Color::Black => ((self as u8) | 1).min(Piece::None as u8),
         })
     }
 
@@ -43,10 +47,6 @@ impl Piece {
         Color::new(color as u8)
     }
 
-    pub fn from_char(c: char) -> Option<Piece> {
-        Some(Self::new("PpNnBbRrQqKk".find(c)? as u8))
-    }
-
     pub fn char(&self) -> Option<char> {
         match self {
             Piece::WhitePawn => Some('P'),
@@ -66,8 +66,56 @@ impl Piece {
     }
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ParsePieceError;
+
+impl Display for ParsePieceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid piece, expected one of PNBRQK or pnbrqk")
+    }
+}
+
+impl Error for ParsePieceError {}
+
+/// Parses the FEN piece letters, uppercase for white and lowercase for black
+impl TryFrom<char> for Piece {
+    type Error = ParsePieceError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match "PpNnBbRrQqKk".find(c) {
+            Some(index) => Ok(Self::new(index as u8)),
+            None => Err(ParsePieceError),
+        }
+    }
+}
+
+impl FromStr for Piece {
+    type Err = ParsePieceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Piece::try_from(c),
+            _ => Err(ParsePieceError),
+        }
+    }
+}
+
+/// Converts indices as used for the piece bitboards. [Piece::None] is not accepted.
+impl TryFrom<u8> for Piece {
+    type Error = ParsePieceError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        if value < 12 {
+            Ok(Self::new(value))
+        } else {
+            Err(ParsePieceError)
+        }
+    }
+}
+
 impl Display for Piece {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let char = match self {
             Piece::WhitePawn => '♙',
             Piece::BlackPawn => '♟',
@@ -107,25 +155,47 @@ mod tests {
     use super::*;
 
     #[test]
-    fn from_char() {
-        assert_eq!(Piece::from_char('P'), Some(Piece::WhitePawn));
-        assert_eq!(Piece::from_char('p'), Some(Piece::BlackPawn));
+    fn try_from_char() {
+        assert_eq!(Piece::try_from('P'), Ok(Piece::WhitePawn));
+        assert_eq!(Piece::try_from('p'), Ok(Piece::BlackPawn));
+
+        assert_eq!(Piece::try_from('N'), Ok(Piece::WhiteKnight));
+        assert_eq!(Piece::try_from('n'), Ok(Piece::BlackKnight));
 
-        assert_eq!(Piece::from_char('N'), Some(Piece::WhiteKnight));
-        assert_eq!(Piece::from_char('n'), Some(Piece::BlackKnight));
+        assert_eq!(Piece::try_from('B'), Ok(Piece::WhiteBishop));
+        assert_eq!(Piece::try_from('b'), Ok(Piece::BlackBishop));
 
-        assert_eq!(Piece::from_char('B'), Some(Piece::WhiteBishop));
-        assert_eq!(Piece::from_char('b'), Some(Piece::BlackBishop));
+        assert_eq!(Piece::try_from('R'), Ok(Piece::WhiteRook));
+        assert_eq!(Piece::try_from('r'), Ok(Piece::BlackRook));
 
-        assert_eq!(Piece::from_char('R'), Some(Piece::WhiteRook));
-        assert_eq!(Piece::from_char('r'), Some(Piece::BlackRook));
+        assert_eq!(Piece::try_from('Q'), Ok(Piece::WhiteQueen));
+        assert_eq!(Piece::try_from('q'), Ok(Piece::BlackQueen));
 
-        assert_eq!(Piece::from_char('Q'), Some(Piece::WhiteQueen));
-        assert_eq!(Piece::from_char('q'), Some(Piece::BlackQueen));
+        assert_eq!(Piece::try_from('K'), Ok(Piece::WhiteKing));
+        assert_eq!(Piece::try_from('k'), Ok(Piece::BlackKing));
 
-        assert_eq!(Piece::from_char('K'), Some(Piece::WhiteKing));
-        assert_eq!(Piece::from_char('k'), Some(Piece::BlackKing));
+        assert_eq!(Piece::try_from('-'), Err(ParsePieceError));
+    }
 
-        assert_eq!(Piece::from_char('-'), None);
+    #[test]
+    fn from_str_and_index() {
+        assert_eq!("q".parse(), Ok(Piece::BlackQueen));
+        assert_eq!("qq".parse::<Piece>(), Err(ParsePieceError));
+        assert_eq!(Piece::try_from(11), Ok(Piece::BlackKing));
+        assert_eq!(Piece::try_from(12), Err(ParsePieceError));
+    }
+
+    #[test]
+    fn with_color() {
+        assert_eq!(
+            Piece::WhiteKnight.with_color(Color::Black),
+            Piece::BlackKnight
+        );
+        assert_eq!(
+            Piece::BlackQueen.with_color(Color::White),
+            Piece::WhiteQueen
+        );
+        assert_eq!(Piece::None.with_color(Color::Black), Piece::None);
+        assert_eq!(Piece::None.with_color(Color::White), Piece::None);
     }
 }
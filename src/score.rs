@@ -0,0 +1,24 @@
+//! Score constants shared by the search and the transposition table, in centipawns.
+
+/// Maximum search depth in plies
+pub const MAX_PLY: usize = 128;
+
+pub const INFINITE: i32 = 32_000;
+pub const MATE: i32 = 31_000;
+
+/// Every score at or above this bound is a mate found within [MAX_PLY] plies
+pub const MATE_IN_MAX_PLY: i32 = MATE - MAX_PLY as i32;
+
+/// Score of delivering mate `ply` plies from the root
+pub fn mate_in(ply: usize) -> i32 {
+    MATE - ply as i32
+}
+
+/// Score of getting mated `ply` plies from the root
+pub fn mated_in(ply: usize) -> i32 {
+    -MATE + ply as i32
+}
+
+pub fn is_mate(score: i32) -> bool {
+    score.abs() >= MATE_IN_MAX_PLY
+}
@@ -0,0 +1,486 @@
+use crate::board::Board;
+use crate::evaluation::piece_value;
+use crate::moves::{MAX_MOVES, Move, MoveList};
+use crate::pawns::PawnTable;
+use crate::score::{INFINITE, MAX_PLY, mated_in};
+use crate::transposition::{Bound, TranspositionTable};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// When to stop searching. Without any limit the search runs until it is stopped from the
+/// outside or reaches [MAX_PLY].
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Limits {
+    pub depth: Option<usize>,
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+}
+
+/// Result of a completed iteration
+#[derive(Clone, Debug)]
+pub struct SearchInfo {
+    pub depth: usize,
+    pub seldepth: usize,
+    /// Score from the point of view of the side to move
+    pub score: i32,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub pv: Vec<Move>,
+}
+
+impl SearchInfo {
+    pub fn best_move(&self) -> Option<Move> {
+        self.pv.first().copied()
+    }
+}
+
+/// Searches `board` with iterative deepening until one of the `limits` is hit or `stop` is
+/// set, calling `report` after every completed iteration.
+///
+/// `history` holds the keys of the positions played before `board` and is used to detect
+/// repetitions. `pawns` caches the pawn structure evaluation and is kept between searches. The
+/// first iteration always completes, so a legal position always yields a best move.
+pub fn search(
+    board: &Board,
+    history: &[u64],
+    limits: Limits,
+    tt: &TranspositionTable,
+    pawns: &mut PawnTable,
+    stop: &AtomicBool,
+    mut report: impl FnMut(&SearchInfo),
+) -> SearchInfo {
+    let mut searcher = Searcher {
+        board: *board,
+        history: history.to_vec(),
+        tt,
+        stop,
+        limits,
+        start: Instant::now(),
+        root_depth: 0,
+        nodes: 0,
+        seldepth: 0,
+        pv: Box::new(PvTable::new()),
+        pawns,
+        stopped: false,
+    };
+
+    tt.new_search();
+
+    let max_depth = limits.depth.unwrap_or(MAX_PLY - 1).clamp(1, MAX_PLY - 1);
+    let mut result = None;
+    for depth in 1..=max_depth {
+        searcher.root_depth = depth;
+        searcher.seldepth = 0;
+
+        let score = searcher.negamax(-INFINITE, INFINITE, depth as i32, 0);
+        if searcher.stopped {
+            break;
+        }
+
+        let info = SearchInfo {
+            depth,
+            seldepth: searcher.seldepth,
+            score,
+            nodes: searcher.nodes,
+            elapsed: searcher.start.elapsed(),
+            pv: searcher.pv.line().to_vec(),
+        };
+        report(&info);
+
+        // Without legal moves there is nothing to deepen
+        let finished = info.pv.is_empty();
+        result = Some(info);
+        if finished {
+            break;
+        }
+    }
+
+    result.expect("The first iteration is never stopped")
+}
+
+/// Triangular table collecting the principal variation, see
+/// [here](https://www.chessprogramming.org/Triangular_PV-Table).
+struct PvTable {
+    moves: [[Move; MAX_PLY]; MAX_PLY + 1],
+    len: [usize; MAX_PLY + 1],
+}
+
+impl PvTable {
+    fn new() -> Self {
+        Self {
+            moves: [[Move::from_raw(0); MAX_PLY]; MAX_PLY + 1],
+            len: [0; MAX_PLY + 1],
+        }
+    }
+
+    fn clear(&mut self, ply: usize) {
+        self.len[ply] = 0;
+    }
+
+    /// Sets the line at `ply` to `mv` followed by the line found below it
+    fn update(&mut self, ply: usize, mv: Move) {
+        let (current, next) = self.moves.split_at_mut(ply + 1);
+        let len = self.len[ply + 1];
+
+        current[ply][0] = mv;
+        current[ply][1..=len].copy_from_slice(&next[0][..len]);
+        self.len[ply] = len + 1;
+    }
+
+    fn line(&self) -> &[Move] {
+        &self.moves[0][..self.len[0]]
+    }
+}
+
+struct Searcher<'a> {
+    board: Board,
+    /// Keys of all positions before the current one, including the search path
+    history: Vec<u64>,
+    tt: &'a TranspositionTable,
+    stop: &'a AtomicBool,
+    limits: Limits,
+    start: Instant,
+    root_depth: usize,
+    nodes: u64,
+    seldepth: usize,
+    pv: Box<PvTable>,
+    pawns: &'a mut PawnTable,
+    stopped: bool,
+}
+
+impl Searcher<'_> {
+    fn negamax(&mut self, mut alpha: i32, beta: i32, depth: i32, ply: usize) -> i32 {
+        self.pv.clear(ply);
+
+        if depth <= 0 {
+            return self.quiescence(alpha, beta, ply);
+        }
+
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+        if self.should_stop() {
+            return 0;
+        }
+
+        if ply > 0 {
+            if self.is_draw() {
+                return 0;
+            }
+            if ply >= MAX_PLY - 1 {
+                return self.board.evaluate_cached(self.pawns);
+            }
+        }
+
+        let pv_node = beta - alpha > 1;
+        let hash = self.board.hash();
+        let tt_entry = self.tt.probe(hash, ply);
+
+        if let Some(entry) = tt_entry
+            && !pv_node
+            && entry.depth >= depth
+        {
+            match entry.bound {
+                Bound::Exact => return entry.score,
+                Bound::Lower if entry.score >= beta => return entry.score,
+                Bound::Upper if entry.score <= alpha => return entry.score,
+                _ => {}
+            }
+        }
+
+        let mut moves = self.board.legal_moves();
+        if moves.is_empty() {
+            return if self.board.in_check() {
+                mated_in(ply)
+            } else {
+                0
+            };
+        }
+
+        let eval = self.board.evaluate_cached(self.pawns);
+        let mut scores = self.score_moves(&moves, tt_entry.and_then(|entry| entry.mv));
+
+        let mut best_score = -INFINITE;
+        let mut best_move = None;
+
+        for i in 0..moves.len() {
+            let mv = pick_next(&mut moves, &mut scores, i);
+
+            let undo = self.board.make_move(mv);
+            self.history.push(hash);
+
+            // Principal variation search: only the first move is searched with the full
+            // window, the others just have to prove that they are worse
+            let score = if i == 0 {
+                -self.negamax(-beta, -alpha, depth - 1, ply + 1)
+            } else {
+                let mut score = -self.negamax(-alpha - 1, -alpha, depth - 1, ply + 1);
+                if score > alpha && score < beta {
+                    score = -self.negamax(-beta, -alpha, depth - 1, ply + 1);
+                }
+                score
+            };
+
+            self.history.pop();
+            self.board.unmake_move(mv, undo);
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+
+                if score > alpha {
+                    alpha = score;
+                    best_move = Some(mv);
+                    self.pv.update(ply, mv);
+
+                    if alpha >= beta {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_move.is_some() {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.tt
+            .store(hash, best_move, best_score, eval, depth, bound, ply);
+
+        best_score
+    }
+
+    /// Resolves captures and promotions until the position is quiet, see
+    /// [here](https://www.chessprogramming.org/Quiescence_Search). In check all evasions are
+    /// searched, since standing pat is not an option. Otherwise captures that lose material by
+    /// [Board::see] are skipped.
+    fn quiescence(&mut self, mut alpha: i32, beta: i32, ply: usize) -> i32 {
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+        if self.should_stop() {
+            return 0;
+        }
+
+        if ply >= MAX_PLY - 1 {
+            return self.board.evaluate_cached(self.pawns);
+        }
+
+        let in_check = self.board.in_check();
+        let mut best_score = -INFINITE;
+
+        if !in_check {
+            best_score = self.board.evaluate_cached(self.pawns);
+            if best_score >= beta {
+                return best_score;
+            }
+            alpha = alpha.max(best_score);
+        }
+
+        let mut moves = self.board.legal_moves();
+        if in_check && moves.is_empty() {
+            return mated_in(ply);
+        }
+
+        let mut scores = self.score_moves(&moves, None);
+        for i in 0..moves.len() {
+            let mv = pick_next(&mut moves, &mut scores, i);
+            if !in_check && !mv.is_capture() && !mv.is_promotion() {
+                // Moves are ordered, so only quiet moves are left
+                break;
+            }
+
+            // Captures losing material are not going to raise alpha
+            if !in_check && !self.board.see(mv, 0) {
+                continue;
+            }
+
+            let undo = self.board.make_move(mv);
+            let score = -self.quiescence(-beta, -alpha, ply + 1);
+            self.board.unmake_move(mv, undo);
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                if score > alpha {
+                    alpha = score;
+                    if alpha >= beta {
+                        break;
+                    }
+                }
+            }
+        }
+
+        best_score
+    }
+
+    /// Orders the transposition table move first, followed by captures and promotions by
+    /// most valuable victim / least valuable attacker and finally quiet moves.
+    fn score_moves(&self, moves: &MoveList, tt_move: Option<Move>) -> [i32; MAX_MOVES] {
+        let mut scores = [0; MAX_MOVES];
+
+        for (score, &mv) in scores.iter_mut().zip(moves.iter()) {
+            if Some(mv) == tt_move {
+                *score = i32::MAX;
+                continue;
+            }
+
+            if mv.is_capture() || mv.is_promotion() {
+                let victim = piece_value(self.board.captured_piece(mv));
+                let attacker = piece_value(self.board.piece_on(mv.from()));
+                let promotion = mv.get_promotion().map_or(0, piece_value);
+                *score = 1_000_000 + 10 * (victim + promotion) - attacker / 10;
+            }
+        }
+
+        scores
+    }
+
+    /// Whether the search has to be aborted. The first iteration always runs to completion.
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+        if self.root_depth == 1 {
+            return false;
+        }
+
+        if self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes) {
+            self.stopped = true;
+        }
+
+        // Checking the clock is comparatively expensive
+        if self.nodes.is_multiple_of(1024)
+            && (self.stop.load(Ordering::Relaxed)
+                || self
+                    .limits
+                    .time
+                    .is_some_and(|time| self.start.elapsed() >= time))
+        {
+            self.stopped = true;
+        }
+
+        self.stopped
+    }
+
+    /// Fifty-move rule or a repetition of any earlier position since the last irreversible
+    /// move. A single repetition is enough, since the side that could avoid it already did
+    /// not.
+    fn is_draw(&self) -> bool {
+        if self.board.half_moves() >= 100 {
+            return true;
+        }
+
+        let hash = self.board.hash();
+        self.history
+            .iter()
+            .rev()
+            .take(self.board.half_moves())
+            .skip(1)
+            .step_by(2)
+            .any(|&key| key == hash)
+    }
+}
+
+/// Selection sort step: moves the best remaining move to `index` and returns it.
+fn pick_next(moves: &mut MoveList, scores: &mut [i32; MAX_MOVES], index: usize) -> Move {
+    let mut best = index;
+    for i in index + 1..moves.len() {
+        if scores[i] > scores[best] {
+            best = i;
+        }
+    }
+
+    moves.swap(index, best);
+    scores.swap(index, best);
+    moves[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::score::mate_in;
+
+    fn search_fen(fen: &str, depth: usize) -> SearchInfo {
+        let board = Board::from_fen(fen).unwrap();
+        let limits = Limits {
+            depth: Some(depth),
+            ..Limits::default()
+        };
+
+        search(
+            &board,
+            &[],
+            limits,
+            &TranspositionTable::new(1),
+            &mut PawnTable::new(1024),
+            &AtomicBool::new(false),
+            |_| {},
+        )
+    }
+
+    #[test]
+    fn finds_mate_in_one() {
+        let info = search_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 3);
+
+        assert_eq!(info.best_move().unwrap().to_string(), "a1a8");
+        assert_eq!(info.score, mate_in(1));
+    }
+
+    #[test]
+    fn finds_mate_in_two() {
+        // 1. Kc7 Ka7 2. Ra1#
+        let info = search_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1", 5);
+
+        assert_eq!(info.score, mate_in(3));
+        assert_eq!(info.pv.len(), 3);
+    }
+
+    #[test]
+    fn wins_hanging_queen() {
+        let info = search_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", 4);
+
+        assert_eq!(info.best_move().unwrap().to_string(), "d2d5");
+        assert!(info.score > 300);
+    }
+
+    #[test]
+    fn no_legal_moves() {
+        let stalemate = search_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 3);
+        assert_eq!(stalemate.best_move(), None);
+        assert_eq!(stalemate.score, 0);
+
+        let checkmate = search_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1", 3);
+        assert_eq!(checkmate.best_move(), None);
+        assert_eq!(checkmate.score, mated_in(0));
+    }
+
+    #[test]
+    fn stops_at_node_limit() {
+        let board = Board::starting_position();
+        let limits = Limits {
+            nodes: Some(10_000),
+            ..Limits::default()
+        };
+
+        let info = search(
+            &board,
+            &[],
+            limits,
+            &TranspositionTable::new(1),
+            &mut PawnTable::new(1024),
+            &AtomicBool::new(false),
+            |_| {},
+        );
+
+        assert!(info.best_move().is_some());
+        assert!(info.nodes <= 10_000);
+    }
+}
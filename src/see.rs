@@ -0,0 +1,105 @@
+use crate::bitboard::{bishop_attacks, rook_attacks};
+use crate::board::Board;
+use crate::color::Color::White;
+use crate::evaluation::piece_value;
+use crate::moves::{Move, MoveType};
+use crate::piece::Piece;
+
+/// Attackers in the order they join an exchange
+const ATTACKER_ORDER: [Piece; 6] = [
+    Piece::WhitePawn,
+    Piece::WhiteKnight,
+    Piece::WhiteBishop,
+    Piece::WhiteRook,
+    Piece::WhiteQueen,
+    Piece::WhiteKing,
+];
+
+impl Board {
+    /// [Static Exchange Evaluation](https://www.chessprogramming.org/Static_Exchange_Evaluation):
+    /// whether `mv` gains at least `threshold` centipawns once all captures on its destination
+    /// are played out. Each side recaptures with its least valuable attacker, sliders behind it
+    /// join through x-rays, and either side may stop when continuing would lose material.
+    ///
+    /// Pins are ignored and recaptures never promote.
+    pub fn see(&self, mv: Move, threshold: i32) -> bool {
+        if mv.ty() == MoveType::Castling {
+            return threshold <= 0;
+        }
+
+        let (from, to) = (mv.from(), mv.to());
+        let promotion = mv.get_promotion();
+
+        let mut balance = piece_value(self.captured_piece(mv)) - threshold;
+        if let Some(promotion) = promotion {
+            balance += piece_value(promotion) - piece_value(Piece::WhitePawn);
+        }
+        if balance < 0 {
+            return false;
+        }
+
+        // Even if the moved piece is lost for nothing, the threshold is reached
+        let moved = promotion.unwrap_or(self.piece_on(from));
+        balance -= piece_value(moved);
+        if balance >= 0 {
+            return true;
+        }
+
+        let mut occupancy = (self.occupancy() ^ from.bitboard()) | to.bitboard();
+        if mv.ty() == MoveType::EnPassant {
+            occupancy ^= (to ^ 8).bitboard();
+        }
+
+        let queens = self.pieces(Piece::WhiteQueen) | self.pieces(Piece::BlackQueen);
+        let diagonal = self.pieces(Piece::WhiteBishop) | self.pieces(Piece::BlackBishop) | queens;
+        let orthogonal = self.pieces(Piece::WhiteRook) | self.pieces(Piece::BlackRook) | queens;
+
+        let mut attackers = self.attackers_to(to, occupancy) & occupancy;
+        let mut color = self.opponent();
+
+        // `color` is the side to capture next. The side that runs out of good captures first
+        // loses the exchange.
+        loop {
+            let our_attackers = attackers & self.colors(color);
+            if our_attackers.is_empty() {
+                break;
+            }
+
+            let attacker = ATTACKER_ORDER
+                .into_iter()
+                .map(|piece| piece.with_color(color))
+                .find(|&piece| (our_attackers & self.pieces(piece)).is_not_empty())
+                .unwrap();
+            occupancy ^= (our_attackers & self.pieces(attacker)).lsb().bitboard();
+
+            // Reveal sliders behind the capturing piece
+            match attacker.with_color(White) {
+                Piece::WhitePawn | Piece::WhiteBishop => {
+                    attackers |= bishop_attacks(to, occupancy) & diagonal;
+                }
+                Piece::WhiteRook => attackers |= rook_attacks(to, occupancy) & orthogonal,
+                Piece::WhiteQueen => {
+                    attackers |= bishop_attacks(to, occupancy) & diagonal;
+                    attackers |= rook_attacks(to, occupancy) & orthogonal;
+                }
+                _ => {}
+            }
+            attackers &= occupancy;
+
+            color = color.other();
+            balance = -balance - 1 - piece_value(attacker);
+
+            if balance >= 0 {
+                // The king may only capture if the square is no longer defended
+                if attacker.with_color(White) == Piece::WhiteKing
+                    && (attackers & self.colors(color)).is_not_empty()
+                {
+                    color = color.other();
+                }
+                break;
+            }
+        }
+
+        color != self.side_to_move()
+    }
+}
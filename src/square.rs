@@ -1,7 +1,9 @@
 use crate::bitboard::Bitboard;
 use crate::board::BOARD_SIZE;
+use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::ops::{BitXor, BitXorAssign, Index, IndexMut};
+use std::str::FromStr;
 
 /// Square representation with a1 = 0.
 ///
@@ -25,28 +27,24 @@ pub enum Square {
 }
 
 impl Square {
-    pub fn new(square: u8) -> Self {
+    /// Unchecked conversion for trusted indices, use [Square::try_from] for untrusted input
+    pub(crate) fn new(square: u8) -> Self {
         debug_assert!(square <= 64);
         unsafe { std::mem::transmute(square) }
     }
 
-    pub fn from_file_and_rank(file: u8, rank: u8) -> Self {
+    /// Unchecked like [Square::new], `file` and `rank` have to be below 8.
+    pub(crate) fn from_file_and_rank(file: u8, rank: u8) -> Self {
         debug_assert!(file < 8);
         debug_assert!(rank < 8);
 
         Self::new(file | (rank << 3))
     }
 
-    /// Returns [Square::None] on invalid input
-    pub fn from_algebraic_notation(notation: &str) -> Self {
-        let b = notation.as_bytes();
-        if b.len() != 2 || b[0] < b'a' || b[0] > b'h' || b[1] < b'1' || b[1] > b'8' {
-            return Self::None;
-        }
-
-        let file = b[0] - b'a';
-        let rank = b[1] - b'1';
-        Self::from_file_and_rank(file, rank)
+    /// Moves the square by `offset` indices. The caller has to make sure the result stays on
+    /// the board.
+    pub(crate) fn shift(self, offset: i8) -> Self {
+        Self::new((self as i8 + offset) as u8)
     }
 
     pub fn bitboard(self) -> Bitboard {
@@ -62,6 +60,44 @@ impl Square {
     }
 }
 
+/// Error for text or indices that do not describe a square on the board
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ParseSquareError;
+
+impl Display for ParseSquareError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid square")
+    }
+}
+
+impl Error for ParseSquareError {}
+
+/// Parses algebraic notation like `e4`
+impl FromStr for Square {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.as_bytes() {
+            &[file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+                Ok(Self::from_file_and_rank(file - b'a', rank - b'1'))
+            }
+            _ => Err(ParseSquareError),
+        }
+    }
+}
+
+impl TryFrom<u8> for Square {
+    type Error = ParseSquareError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        if value < 64 {
+            Ok(Self::new(value))
+        } else {
+            Err(ParseSquareError)
+        }
+    }
+}
+
 impl Display for Square {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         if *self as u8 > 64 {
@@ -91,16 +127,52 @@ impl<T> IndexMut<Square> for [T] {
     }
 }
 
+/// Flips bits of the square index, e.g. `^ 8` for the square behind an en passant target or
+/// `^ 56` to mirror vertically. Only the low six bits are used, so the result is always on the
+/// board.
 impl BitXor<u8> for Square {
     type Output = Square;
 
     fn bitxor(self, rhs: u8) -> Self::Output {
-        Self::new(self as u8 ^ rhs)
+        Self::new((self as u8 ^ rhs) & 0b111111)
     }
 }
 
 impl BitXorAssign<u8> for Square {
     fn bitxor_assign(&mut self, rhs: u8) {
-        *self = Self::new(*self as u8 ^ rhs);
+        *self = *self ^ rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_square() {
+        assert_eq!("a1".parse(), Ok(Square::A1));
+        assert_eq!("h8".parse(), Ok(Square::H8));
+        assert_eq!(
+            "e4".parse::<Square>().map(|s| s.to_string()),
+            Ok("e4".to_string())
+        );
+
+        for text in ["", "e", "e9", "i1", "E4", "e44", "-"] {
+            assert_eq!(text.parse::<Square>(), Err(ParseSquareError), "{text}");
+        }
+    }
+
+    #[test]
+    fn square_from_index() {
+        assert_eq!(Square::try_from(0), Ok(Square::A1));
+        assert_eq!(Square::try_from(63), Ok(Square::H8));
+        assert_eq!(Square::try_from(64), Err(ParseSquareError));
+    }
+
+    #[test]
+    fn xor_stays_on_board() {
+        assert_eq!(Square::E3 ^ 8, Square::E4);
+        assert_eq!(Square::A1 ^ 56, Square::A8);
+        assert_eq!(Square::A1 ^ 200, Square::A2);
     }
 }
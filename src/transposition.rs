@@ -0,0 +1,335 @@
+use crate::moves::Move;
+use crate::score::MATE_IN_MAX_PLY;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+/// How a stored score relates to the true score of the position
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[repr(u8)]
+pub enum Bound {
+    None = 0,
+    /// Failed low, the true score is at most the stored one
+    Upper = 1,
+    /// Failed high, the true score is at least the stored one
+    Lower = 2,
+    Exact = 3,
+}
+
+impl Bound {
+    fn from_bits(bits: u64) -> Self {
+        match bits & 0b11 {
+            1 => Bound::Upper,
+            2 => Bound::Lower,
+            3 => Bound::Exact,
+            _ => Bound::None,
+        }
+    }
+}
+
+/// A decoded transposition table entry. Mate scores are relative to the probing node.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct TtEntry {
+    pub mv: Option<Move>,
+    pub score: i32,
+    pub eval: i32,
+    pub depth: i32,
+    pub bound: Bound,
+}
+
+/// Number of search generations before the age wraps around
+const AGE_CYCLE: u8 = 1 << 6;
+
+/// # Memory Layout
+/// \[AGE: 6 bit]\[BOUND: 2 bit]\[DEPTH: 8 bit]\[EVAL: 16 bit]\[SCORE: 16 bit]\[MOVE: 16 bit]
+///
+/// The key is stored xored with the data, so a slot torn by two threads writing at the same
+/// time simply fails to match on the next probe, see
+/// [here](https://www.chessprogramming.org/Shared_Hash_Table#Lock-less).
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl Slot {
+    fn load(&self) -> (u64, u64) {
+        let data = self.data.load(Ordering::Relaxed);
+        let key = self.key.load(Ordering::Relaxed) ^ data;
+        (key, data)
+    }
+
+    fn save(&self, key: u64, data: u64) {
+        self.key.store(key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+const BUCKET_SIZE: usize = 4;
+
+/// Four slots filling exactly one cache line
+#[derive(Default)]
+#[repr(align(64))]
+struct Bucket {
+    slots: [Slot; BUCKET_SIZE],
+}
+
+fn pack(mv: Option<Move>, score: i32, eval: i32, depth: i32, bound: Bound, age: u8) -> u64 {
+    mv.map_or(0, Move::raw) as u64
+        | (score as i16 as u16 as u64) << 16
+        | (eval as i16 as u16 as u64) << 32
+        | (depth.clamp(0, u8::MAX as i32) as u64) << 48
+        | (bound as u64) << 56
+        | (age as u64) << 58
+}
+
+fn unpack(data: u64) -> TtEntry {
+    let mv = data as u16;
+    TtEntry {
+        mv: (mv != 0).then(|| Move::from_raw(mv)),
+        score: (data >> 16) as u16 as i16 as i32,
+        eval: (data >> 32) as u16 as i16 as i32,
+        depth: (data >> 48) as u8 as i32,
+        bound: Bound::from_bits(data >> 56),
+    }
+}
+
+fn depth_of(data: u64) -> i32 {
+    (data >> 48) as u8 as i32
+}
+
+fn age_of(data: u64) -> u8 {
+    (data >> 58) as u8
+}
+
+/// Mate scores are stored relative to the node instead of the root, so they stay correct when
+/// the position is reached again at a different ply.
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_IN_MAX_PLY {
+        score + ply as i32
+    } else if score <= -MATE_IN_MAX_PLY {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_IN_MAX_PLY {
+        score - ply as i32
+    } else if score <= -MATE_IN_MAX_PLY {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+/// Hash table of previously searched positions, indexed by Zobrist key.
+///
+/// All operations except resizing take `&self`, so the table can be shared between the search
+/// and the thread driving it.
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub const DEFAULT_SIZE_MB: usize = 16;
+
+    pub fn new(size_mb: usize) -> Self {
+        let mut table = Self {
+            buckets: Vec::new(),
+            age: AtomicU8::new(0),
+        };
+        table.resize(size_mb);
+        table
+    }
+
+    /// Reallocates the table with the given size in megabytes, dropping all entries.
+    pub fn resize(&mut self, size_mb: usize) {
+        let len = (size_mb * 1024 * 1024 / size_of::<Bucket>()).max(1);
+        self.buckets = Vec::new();
+        self.buckets.resize_with(len, Bucket::default);
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        for bucket in &self.buckets {
+            for slot in &bucket.slots {
+                slot.save(0, 0);
+            }
+        }
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    /// Starts a new search generation, making all existing entries preferred replacement
+    /// targets.
+    pub fn new_search(&self) {
+        let age = self.age.load(Ordering::Relaxed);
+        self.age.store((age + 1) % AGE_CYCLE, Ordering::Relaxed);
+    }
+
+    pub fn probe(&self, hash: u64, ply: usize) -> Option<TtEntry> {
+        self.bucket(hash).slots.iter().find_map(|slot| {
+            let (key, data) = slot.load();
+            let mut entry = unpack(data);
+            if key != hash || entry.bound == Bound::None {
+                return None;
+            }
+
+            entry.score = score_from_tt(entry.score, ply);
+            Some(entry)
+        })
+    }
+
+    /// Stores a search result. An entry of the same position is overwritten unless it holds a
+    /// deeper result of the current search, otherwise the shallowest and oldest entry of the
+    /// bucket is replaced.
+    #[allow(clippy::too_many_arguments)]
+    pub fn store(
+        &self,
+        hash: u64,
+        mv: Option<Move>,
+        score: i32,
+        eval: i32,
+        depth: i32,
+        bound: Bound,
+        ply: usize,
+    ) {
+        let age = self.age.load(Ordering::Relaxed);
+        let bucket = self.bucket(hash);
+
+        let existing = bucket.slots.iter().find(|slot| {
+            let (key, data) = slot.load();
+            key == hash && Bound::from_bits(data >> 56) != Bound::None
+        });
+
+        let (slot, mv) = match existing {
+            Some(slot) => {
+                let (_, old) = slot.load();
+                if bound != Bound::Exact && age_of(old) == age && depth + 4 < depth_of(old) {
+                    return;
+                }
+                // Keep the old move if the new search did not produce one
+                (slot, mv.or(unpack(old).mv))
+            }
+            None => {
+                let slot = bucket
+                    .slots
+                    .iter()
+                    .min_by_key(|slot| {
+                        let (_, data) = slot.load();
+                        if Bound::from_bits(data >> 56) == Bound::None {
+                            return i32::MIN;
+                        }
+                        let relative_age = (AGE_CYCLE + age - age_of(data)) % AGE_CYCLE;
+                        depth_of(data) - 8 * relative_age as i32
+                    })
+                    .unwrap();
+                (slot, mv)
+            }
+        };
+
+        slot.save(
+            hash,
+            pack(mv, score_to_tt(score, ply), eval, depth, bound, age),
+        );
+    }
+
+    /// Permille of sampled slots filled during the current search, as reported by UCI
+    /// `hashfull`
+    pub fn hashfull(&self) -> usize {
+        let age = self.age.load(Ordering::Relaxed);
+        let used = self
+            .buckets
+            .iter()
+            .take(1000 / BUCKET_SIZE)
+            .flat_map(|bucket| &bucket.slots)
+            .filter(|slot| {
+                let (_, data) = slot.load();
+                Bound::from_bits(data >> 56) != Bound::None && age_of(data) == age
+            })
+            .count();
+
+        used * 1000 / (self.buckets.len().min(1000 / BUCKET_SIZE) * BUCKET_SIZE)
+    }
+
+    fn bucket(&self, hash: u64) -> &Bucket {
+        // Maps the hash onto the table without requiring a power of two size
+        let index = ((hash as u128 * self.buckets.len() as u128) >> 64) as usize;
+        &self.buckets[index]
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_SIZE_MB)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::MoveType;
+    use crate::score::mate_in;
+    use crate::square::Square;
+
+    #[test]
+    fn store_and_probe() {
+        let tt = TranspositionTable::new(1);
+        let mv = Move::new(Square::E7, Square::E8, MoveType::PromotionCaptureQueen);
+
+        tt.store(0xDEAD_BEEF, Some(mv), -150, 42, 7, Bound::Lower, 3);
+
+        let entry = tt.probe(0xDEAD_BEEF, 3).unwrap();
+        assert_eq!(
+            entry,
+            TtEntry {
+                mv: Some(mv),
+                score: -150,
+                eval: 42,
+                depth: 7,
+                bound: Bound::Lower,
+            }
+        );
+        assert!(tt.probe(0xDEAD_BEEE, 3).is_none());
+
+        tt.clear();
+        assert!(tt.probe(0xDEAD_BEEF, 3).is_none());
+    }
+
+    #[test]
+    fn mate_scores_are_relative_to_the_node() {
+        let tt = TranspositionTable::new(1);
+
+        // Mate in 5 plies from the root, found at ply 2
+        tt.store(1, None, mate_in(5), 0, 4, Bound::Exact, 2);
+
+        // Reached again at ply 4 the mate is two plies further away from the root
+        assert_eq!(tt.probe(1, 4).unwrap().score, mate_in(7));
+        assert_eq!(tt.probe(1, 2).unwrap().score, mate_in(5));
+    }
+
+    #[test]
+    fn old_entries_are_replaced_first() {
+        let tt = TranspositionTable::new(1);
+        // All keys map to the first bucket
+        let keys = [1, 2, 3, 4, 5];
+
+        for &key in &keys[..4] {
+            tt.store(key, None, 0, 0, 20, Bound::Exact, 0);
+        }
+        tt.new_search();
+        tt.store(keys[0], None, 0, 0, 20, Bound::Exact, 0);
+        tt.store(keys[4], None, 0, 0, 1, Bound::Exact, 0);
+
+        // The refreshed entry survives while one of the old, deeper ones is evicted
+        assert!(tt.probe(keys[0], 0).is_some());
+        assert!(tt.probe(keys[4], 0).is_some());
+        assert_eq!(
+            keys[1..4]
+                .iter()
+                .filter(|&&key| tt.probe(key, 0).is_some())
+                .count(),
+            2
+        );
+    }
+}
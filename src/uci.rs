@@ -0,0 +1,443 @@
+use crate::board::Board;
+use crate::game::Game;
+use crate::moves::Move;
+use crate::pawns::PawnTable;
+use crate::score::{MATE, is_mate};
+use crate::search::{Limits, SearchInfo, search};
+use crate::transposition::TranspositionTable;
+use std::io::BufRead;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Time reserved for communication with the GUI on every move
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+
+/// Assumed number of remaining moves when the GUI does not send `movestogo`
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+const MAX_HASH_MB: usize = 65_536;
+
+/// State of the [UCI](https://www.shredderchess.com/download/div/uci.zip) protocol loop. The
+/// search runs on its own thread so `stop` and `isready` are answered while it is thinking.
+pub struct Uci {
+    game: Game,
+    /// `UCI_Chess960`: castling moves are sent as the king capturing its own rook
+    chess960: bool,
+    tt: Arc<TranspositionTable>,
+    /// Only used by the search thread, but kept across searches
+    pawns: Arc<Mutex<PawnTable>>,
+    stop: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+}
+
+impl Uci {
+    pub fn new() -> Self {
+        Self {
+            game: Game::default(),
+            chess960: false,
+            tt: Arc::new(TranspositionTable::default()),
+            pawns: Arc::new(Mutex::new(PawnTable::default())),
+            stop: Arc::new(AtomicBool::new(false)),
+            search: None,
+        }
+    }
+
+    /// Reads commands from stdin until `quit` or the end of the input.
+    pub fn run(&mut self) {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if !self.handle(&line) {
+                break;
+            }
+        }
+
+        self.stop_search();
+    }
+
+    /// Executes a single command and returns whether the loop should continue.
+    fn handle(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, args)) = tokens.split_first() else {
+            return true;
+        };
+
+        match command {
+            "uci" => {
+                println!("id name croissant-chess {}", env!("CARGO_PKG_VERSION"));
+                println!("id author GreenOpposite");
+                println!(
+                    "option name Hash type spin default {} min 1 max {MAX_HASH_MB}",
+                    TranspositionTable::DEFAULT_SIZE_MB
+                );
+                println!("option name UCI_Chess960 type check default false");
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "ucinewgame" => {
+                self.stop_search();
+                self.tt.clear();
+                self.pawns.lock().unwrap().clear();
+            }
+            "position" => self.position(args),
+            "go" => self.go(args),
+            "stop" => self.stop_search(),
+            "setoption" => self.set_option(args),
+            // Not part of UCI, prints the evaluation of the current position
+            "eval" => print!("{}", self.game.board().trace()),
+            "quit" => return false,
+            _ => println!("info string Unknown command: {command}"),
+        }
+
+        true
+    }
+
+    /// `position [startpos | fen <fen>] [moves <move>...]`
+    fn position(&mut self, args: &[&str]) {
+        let moves_start = args
+            .iter()
+            .position(|&token| token == "moves")
+            .unwrap_or(args.len());
+        let (setup, moves) = args.split_at(moves_start);
+
+        let board = match setup {
+            ["startpos"] => Board::starting_position(),
+            ["fen", fen @ ..] => {
+                let fen = fen.join(" ");
+                let board = if self.chess960 {
+                    Board::from_fen_chess960(&fen)
+                } else {
+                    Board::from_fen(&fen)
+                };
+                match board {
+                    Ok(board) => board,
+                    Err(error) => {
+                        println!("info string Invalid FEN: {error}");
+                        return;
+                    }
+                }
+            }
+            _ => {
+                println!("info string Expected startpos or fen");
+                return;
+            }
+        };
+
+        self.game = Game::new(board);
+
+        for &text in moves.iter().skip(1) {
+            let mv = match self.game.board().parse_uci_move(text) {
+                Ok(mv) => mv,
+                Err(error) => {
+                    println!("info string Invalid move {text}: {error}");
+                    return;
+                }
+            };
+
+            self.game.make_move(mv);
+        }
+    }
+
+    /// `go [wtime|btime|winc|binc|movestogo|depth|nodes|movetime <n>]... [infinite]`
+    fn go(&mut self, args: &[&str]) {
+        self.stop_search();
+
+        let params = GoParams::parse(args);
+        let limits = params.limits(self.game.board());
+        let board = *self.game.board();
+        let history = self.game.history().to_vec();
+        let tt = Arc::clone(&self.tt);
+        let pawns = Arc::clone(&self.pawns);
+        let stop = Arc::clone(&self.stop);
+        let chess960 = self.chess960;
+
+        self.stop.store(false, Ordering::Relaxed);
+        self.search = Some(std::thread::spawn(move || {
+            let mut pawns = pawns.lock().unwrap();
+            let result = search(&board, &history, limits, &tt, &mut pawns, &stop, |info| {
+                print_info(info, &tt, chess960)
+            });
+
+            // In infinite mode the best move may only be sent after `stop`
+            while params.infinite && !stop.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+
+            match result.best_move() {
+                Some(mv) => println!("bestmove {}", format_move(mv, chess960)),
+                None => println!("bestmove 0000"),
+            }
+        }));
+    }
+
+    /// `setoption name <name> [value <value>]`
+    fn set_option(&mut self, args: &[&str]) {
+        let value_start = args
+            .iter()
+            .position(|&token| token == "value")
+            .unwrap_or(args.len());
+        let name = args[..value_start]
+            .iter()
+            .skip_while(|&&token| token == "name")
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let value = args.get(value_start + 1..).unwrap_or_default().join(" ");
+
+        match name.to_lowercase().as_str() {
+            "hash" => match value.parse::<usize>() {
+                Ok(size_mb) if (1..=MAX_HASH_MB).contains(&size_mb) => {
+                    self.stop_search();
+                    Arc::get_mut(&mut self.tt)
+                        .expect("No search is running")
+                        .resize(size_mb);
+                }
+                _ => println!("info string Invalid Hash value: {value}"),
+            },
+            "uci_chess960" => match value.as_str() {
+                "true" => self.chess960 = true,
+                "false" => self.chess960 = false,
+                _ => println!("info string Invalid UCI_Chess960 value: {value}"),
+            },
+            _ => println!("info string Unknown option: {name}"),
+        }
+    }
+
+    /// Stops a running search and waits until it printed its best move.
+    fn stop_search(&mut self) {
+        if let Some(handle) = self.search.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            handle.join().expect("Search thread panicked");
+        }
+    }
+}
+
+impl Default for Uci {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parameters of the `go` command. Times are in milliseconds and indexed by color.
+#[derive(Copy, Clone, Default, Debug)]
+struct GoParams {
+    time: [Option<u64>; 2],
+    increment: [Option<u64>; 2],
+    moves_to_go: Option<u64>,
+    depth: Option<u64>,
+    nodes: Option<u64>,
+    move_time: Option<u64>,
+    infinite: bool,
+}
+
+/// Every argument `go` may have in the UCI protocol, including the unsupported ones
+const GO_KEYWORDS: [&str; 12] = [
+    "searchmoves",
+    "ponder",
+    "wtime",
+    "btime",
+    "winc",
+    "binc",
+    "movestogo",
+    "depth",
+    "nodes",
+    "mate",
+    "movetime",
+    "infinite",
+];
+
+impl GoParams {
+    /// Parses the arguments of `go`. Values are only read after the keys that take one, unknown
+    /// flags like `ponder` are skipped, and so is `searchmoves` together with its moves.
+    fn parse(args: &[&str]) -> Self {
+        let mut params = Self::default();
+        let mut tokens = args.iter().copied().peekable();
+
+        while let Some(token) = tokens.next() {
+            let value = match token {
+                "wtime" => &mut params.time[0],
+                "btime" => &mut params.time[1],
+                "winc" => &mut params.increment[0],
+                "binc" => &mut params.increment[1],
+                "movestogo" => &mut params.moves_to_go,
+                "depth" => &mut params.depth,
+                "nodes" => &mut params.nodes,
+                "movetime" => &mut params.move_time,
+                "infinite" => {
+                    params.infinite = true;
+                    continue;
+                }
+                "searchmoves" => {
+                    while tokens
+                        .next_if(|token| !GO_KEYWORDS.contains(token))
+                        .is_some()
+                    {}
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if let Some(number) = tokens.next_if(|token| token.parse::<u64>().is_ok()) {
+                *value = number.parse().ok();
+            }
+        }
+
+        params
+    }
+
+    fn limits(&self, board: &Board) -> Limits {
+        let mut limits = Limits {
+            depth: self.depth.map(|depth| depth as usize),
+            nodes: self.nodes,
+            time: None,
+        };
+
+        if self.infinite {
+            return limits;
+        }
+
+        let us = board.side_to_move();
+        if let Some(move_time) = self.move_time {
+            limits.time = Some(Duration::from_millis(move_time).saturating_sub(MOVE_OVERHEAD));
+        } else if let Some(time) = self.time[us] {
+            let time = Duration::from_millis(time);
+            let increment = Duration::from_millis(self.increment[us].unwrap_or(0));
+            let moves_to_go = self
+                .moves_to_go
+                .map_or(DEFAULT_MOVES_TO_GO, |moves| moves.max(1) as u32);
+
+            let budget = time / moves_to_go + increment * 3 / 4;
+            limits.time = Some(budget.min(time.saturating_sub(MOVE_OVERHEAD)));
+        }
+
+        limits
+    }
+}
+
+fn print_info(info: &SearchInfo, tt: &TranspositionTable, chess960: bool) {
+    let pv = info
+        .pv
+        .iter()
+        .map(|&mv| format_move(mv, chess960))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    println!(
+        "info depth {} seldepth {} score {} nodes {} nps {} hashfull {} time {} pv {pv}",
+        info.depth,
+        info.seldepth,
+        format_score(info.score),
+        info.nodes,
+        (info.nodes as f64 / info.elapsed.as_secs_f64().max(1e-3)) as u64,
+        tt.hashfull(),
+        info.elapsed.as_millis(),
+    );
+}
+
+/// Formats a move in long algebraic notation, with castling as king takes rook in Chess960 mode.
+fn format_move(mv: Move, chess960: bool) -> String {
+    if chess960 {
+        format!("{mv:#}")
+    } else {
+        mv.to_string()
+    }
+}
+
+/// Formats a score as `cp <centipawns>` or `mate <moves>`, with negative moves if the side to
+/// move gets mated.
+fn format_score(score: i32) -> String {
+    if !is_mate(score) {
+        return format!("cp {score}");
+    }
+
+    let moves = (MATE - score.abs() + 1) / 2;
+    format!("mate {}", if score > 0 { moves } else { -moves })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::score::{mate_in, mated_in};
+
+    #[test]
+    fn mate_scores_in_moves() {
+        assert_eq!(format_score(35), "cp 35");
+        assert_eq!(format_score(mate_in(1)), "mate 1");
+        assert_eq!(format_score(mate_in(3)), "mate 2");
+        assert_eq!(format_score(mated_in(2)), "mate -1");
+        assert_eq!(format_score(mated_in(0)), "mate 0");
+    }
+
+    #[test]
+    fn position_with_moves() {
+        let mut uci = Uci::new();
+        uci.handle("position startpos moves e2e4 e7e5 g1f3");
+
+        assert_eq!(
+            uci.game.board().fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+        );
+        assert_eq!(uci.game.history().len(), 3);
+    }
+
+    #[test]
+    fn chess960_castling() {
+        let mut uci = Uci::new();
+        uci.handle("setoption name UCI_Chess960 value true");
+        assert!(uci.chess960);
+
+        // X-FEN castling rights are only read in Chess960 mode
+        let x_fen = "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1";
+        uci.handle(&format!("position fen {x_fen}"));
+        assert_eq!(uci.game.board().fen(), x_fen);
+
+        // King and rook swap squares
+        uci.handle("position fen 4k3/8/8/8/8/8/8/1R3KR1 w GB - 0 1 moves f1g1");
+        assert_eq!(uci.game.board().fen(), "4k3/8/8/8/8/8/8/1R3RK1 b - - 1 1");
+
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let castling = board.parse_uci_move("e1g1").unwrap();
+        assert_eq!(board.parse_uci_move("e1h1"), Ok(castling));
+        assert_eq!(format_move(castling, false), "e1g1");
+        assert_eq!(format_move(castling, true), "e1h1");
+    }
+
+    #[test]
+    fn go_params() {
+        let params = GoParams::parse(&["ponder", "wtime", "3000", "btime", "2000", "winc", "10"]);
+        assert_eq!(params.time, [Some(3_000), Some(2_000)]);
+        assert_eq!(params.increment, [Some(10), None]);
+
+        let params = GoParams::parse(&["searchmoves", "e2e4", "d2d4", "depth", "5", "infinite"]);
+        assert_eq!(params.depth, Some(5));
+        assert!(params.infinite);
+
+        // A missing value does not swallow the next key
+        let params = GoParams::parse(&["movetime", "nodes", "100", "mate", "3"]);
+        assert_eq!(params.move_time, None);
+        assert_eq!(params.nodes, Some(100));
+    }
+
+    #[test]
+    fn time_limits() {
+        let board = Board::starting_position();
+        let params = GoParams {
+            time: [Some(60_000), Some(1_000)],
+            increment: [Some(1_000), None],
+            ..GoParams::default()
+        };
+
+        assert_eq!(
+            params.limits(&board).time,
+            Some(Duration::from_millis(2_000 + 750))
+        );
+
+        let params = GoParams {
+            move_time: Some(1_000),
+            infinite: true,
+            ..GoParams::default()
+        };
+        assert_eq!(params.limits(&board).time, None);
+    }
+}
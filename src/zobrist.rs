@@ -0,0 +1,81 @@
+use crate::castling_rights::CastlingRights;
+use crate::color::Color;
+use crate::piece::Piece;
+use crate::square::Square;
+
+/// Random keys for [Zobrist hashing](https://www.chessprogramming.org/Zobrist_Hashing).
+pub struct Zobrist {
+    pieces: [[u64; 64]; 12],
+    side: u64,
+    castling: [u64; 16],
+    en_passant: [u64; 8],
+}
+
+pub static ZOBRIST: Zobrist = Zobrist::new(0x5EED_C401_55A7_0000);
+
+impl Zobrist {
+    const fn new(seed: u64) -> Self {
+        let mut state = seed;
+        let mut zobrist = Self {
+            pieces: [[0; 64]; 12],
+            side: 0,
+            castling: [0; 16],
+            en_passant: [0; 8],
+        };
+
+        let mut piece = 0;
+        while piece < 12 {
+            let mut square = 0;
+            while square < 64 {
+                zobrist.pieces[piece][square] = split_mix(&mut state);
+                square += 1;
+            }
+            piece += 1;
+        }
+
+        zobrist.side = split_mix(&mut state);
+
+        let mut i = 0;
+        while i < 16 {
+            zobrist.castling[i] = split_mix(&mut state);
+            i += 1;
+        }
+
+        let mut file = 0;
+        while file < 8 {
+            zobrist.en_passant[file] = split_mix(&mut state);
+            file += 1;
+        }
+
+        zobrist
+    }
+
+    pub fn piece(&self, piece: Piece, square: Square) -> u64 {
+        self.pieces[piece][square]
+    }
+
+    /// Toggled whenever black is to move
+    pub fn side(&self, color: Color) -> u64 {
+        match color {
+            Color::White => 0,
+            Color::Black => self.side,
+        }
+    }
+
+    pub fn castling(&self, castling_rights: CastlingRights) -> u64 {
+        self.castling[castling_rights.raw() as usize]
+    }
+
+    pub fn en_passant(&self, square: Square) -> u64 {
+        self.en_passant[square.file() as usize]
+    }
+}
+
+/// [SplitMix64](https://prng.di.unimi.it/splitmix64.c) step, usable at compile time
+const fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
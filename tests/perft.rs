@@ -0,0 +1,83 @@
+//! Perft regression suite. Every position in `data/perftsuite.epd` lists the known number of
+//! leaf nodes for depths 1 to 6 (`;D<depth> <nodes>`, only up to 5 for a few), and each count is
+//! checked against [Board::perft].
+//!
+//! The file holds the standard perftsuite positions followed by 240 of the 960 Chess960 start
+//! positions, the latter in Shredder-FEN so that their castling rooks are unambiguous.
+//!
+//! `cargo test` only runs the depths below [QUICK_NODE_LIMIT]. The full suite takes a while
+//! and should be run in release mode:
+//! ```sh
+//! cargo test --release --test perft -- --ignored
+//! ```
+
+use croissant_chess::Board;
+
+const SUITE: &str = include_str!("data/perftsuite.epd");
+
+/// Largest node count checked by the quick subset
+const QUICK_NODE_LIMIT: u64 = 250_000;
+
+struct Entry<'a> {
+    fen: &'a str,
+    /// `(depth, nodes)` pairs
+    counts: Vec<(usize, u64)>,
+}
+
+fn parse_suite() -> Vec<Entry<'static>> {
+    SUITE
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut fields = line.split(';');
+            let fen = fields.next().unwrap().trim();
+            let counts = fields
+                .map(|field| {
+                    let (depth, nodes) = field.trim().split_once(' ').unwrap();
+                    let depth = depth.strip_prefix('D').unwrap().parse().unwrap();
+                    (depth, nodes.parse().unwrap())
+                })
+                .collect();
+
+            Entry { fen, counts }
+        })
+        .collect()
+}
+
+fn perft(fen: &str, depth: usize) -> u64 {
+    let mut board = Board::from_fen(fen).unwrap_or_else(|error| panic!("{fen}: {error}"));
+    board.perft(depth)
+}
+
+fn run_suite(node_limit: u64) {
+    let mut failures = Vec::new();
+
+    for entry in parse_suite() {
+        for &(depth, expected) in &entry.counts {
+            if expected > node_limit {
+                continue;
+            }
+
+            let nodes = perft(entry.fen, depth);
+            if nodes != expected {
+                failures.push(format!(
+                    "{} depth {depth}: expected {expected}, got {nodes}",
+                    entry.fen
+                ));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn perft_suite_quick() {
+    run_suite(QUICK_NODE_LIMIT);
+}
+
+#[test]
+#[ignore]
+fn perft_suite_full() {
+    run_suite(u64::MAX);
+}
@@ -0,0 +1,29 @@
+//! Static exchange evaluation against the positions in `data/see.epd`. Every line holds a FEN,
+//! a move in UCI notation and the exact material the move gains (`<fen> ;<move> ;<gain>`), so
+//! [Board::see] has to pass at the gain and fail one centipawn above it.
+
+use croissant_chess::Board;
+
+const SUITE: &str = include_str!("data/see.epd");
+
+#[test]
+fn see_suite() {
+    let mut failures = Vec::new();
+
+    for line in SUITE.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+        let [fen, text, gain] = fields[..] else {
+            panic!("Malformed line: {line}");
+        };
+
+        let board = Board::from_fen(fen).unwrap();
+        let mv = board.parse_uci_move(text).unwrap();
+        let gain: i32 = gain.parse().unwrap();
+
+        if !board.see(mv, gain) || board.see(mv, gain + 1) {
+            failures.push(format!("{fen} {text}: expected {gain}"));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}